@@ -0,0 +1,333 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use edgedb_derive::Queryable;
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+use crate::analyze::contexts::Number;
+use crate::analyze::model::{Analysis, Context, DebugNode, Plan, PropValue};
+use crate::analyze::table;
+use crate::commands::get_indexes;
+use crate::connect::Connection;
+use crate::print::Highlight;
+
+
+/// Sequential scans over fewer rows than this are considered cheap
+const LARGE_RELATION_ROWS: f64 = 1000.;
+
+static UUID: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"[0-9a-f]{8}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{12}")
+        .unwrap()
+});
+
+#[derive(Queryable)]
+struct Pointer {
+    id: uuid::Uuid,
+    name: String,
+    type_id: uuid::Uuid,
+    type_name: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Kind {
+    Filter,
+    Sort,
+}
+
+#[derive(Debug)]
+struct Scan<'a> {
+    kind: Kind,
+    context: Option<Number>,
+    relation: &'a str,
+    expr: &'a str,
+    rows: f64,
+}
+
+#[derive(Debug)]
+struct Advice {
+    kind: Kind,
+    context: Option<Number>,
+    type_name: String,
+    index: String,
+    rows: f64,
+}
+
+struct Schema {
+    types: BTreeMap<String, String>,
+    pointers: BTreeMap<String, Vec<(uuid::Uuid, String)>>,
+}
+
+pub async fn print(cli: &mut Connection, explain: &Analysis)
+    -> anyhow::Result<()>
+{
+    let mut scans = Vec::new();
+    let debug_plan = explain.debug_info.as_ref()
+        .and_then(|d| d.full_plan.as_ref());
+    if let Some(node) = debug_plan {
+        visit_debug(explain, node, None, None, &mut scans);
+    } else if let Some(plan) = &explain.fine_grained {
+        visit_plan(explain, plan, None, &mut scans);
+    }
+    if scans.is_empty() {
+        eprintln!("No sequential scans over large relations found.");
+        return Ok(());
+    }
+
+    let schema = Schema::fetch(cli).await?;
+    let existing = get_indexes(cli, &None, false, false, true).await?
+        .into_iter()
+        .map(|idx| (idx.subject_name, normalize_expr(&idx.expr)))
+        .collect::<BTreeSet<_>>();
+
+    let mut seen = BTreeSet::new();
+    let mut advices = Vec::new();
+    let mut covered = Vec::new();
+    for scan in scans {
+        let Some((type_name, index)) = schema.index_for(&scan) else {
+            continue;
+        };
+        if !seen.insert((type_name.clone(), index.clone())) {
+            continue;
+        }
+        if existing.contains(&(type_name.clone(), normalize_expr(&index))) {
+            covered.push((type_name, index));
+            continue;
+        }
+        advices.push(Advice {
+            kind: scan.kind,
+            context: scan.context,
+            type_name,
+            index,
+            rows: scan.rows,
+        });
+    }
+
+    table::print_title("Index Suggestions", 80);
+    if advices.is_empty() {
+        println!("No new indexes to suggest.");
+    }
+    for advice in &advices {
+        let what = match advice.kind {
+            Kind::Filter => "filters",
+            Kind::Sort => "sorts",
+        };
+        let context = match advice.context {
+            Some(num) => num.to_string(),
+            None => String::new(),
+        };
+        println!("{context}{} sequentially scans and {what} about {} rows",
+                 advice.type_name.emphasize(), advice.rows.round());
+        if let Some(text) = advice.context.and_then(|n| context_text(explain, n)) {
+            println!("  {} {}", "at".fade(), text.trim());
+        }
+        println!("  {}", "Consider adding:".fade());
+        println!("    alter type {} {{", advice.type_name);
+        println!("        create index on ({});", advice.index);
+        println!("    }};");
+        println!();
+    }
+    for (type_name, index) in covered {
+        println!("{}", format!(
+            "Index on ({index}) of {type_name} already exists \
+             but is not used by the query plan.").fade());
+    }
+    Ok(())
+}
+
+fn visit_debug<'a>(explain: &Analysis, node: &'a DebugNode,
+                   context: Option<Number>, sort: Option<&'a str>,
+                   result: &mut Vec<Scan<'a>>)
+{
+    let context = context_number(explain, &node.contexts).or(context);
+    if node.node_type == "Seq Scan" {
+        if let Some(relation) = &node.relation_name {
+            let removed = node.properties.get("rows_removed_by_filter")
+                .and_then(|v| v.as_f64())
+                .unwrap_or(0.);
+            let rows = match node.cost.actual_rows {
+                Some(rows) => {
+                    (rows + removed) * node.cost.actual_loops.unwrap_or(1.)
+                }
+                None => node.cost.plan_rows as f64,
+            };
+            if rows >= LARGE_RELATION_ROWS {
+                let filter = node.properties.get("filter")
+                    .and_then(|v| v.as_str());
+                if let Some(expr) = filter {
+                    result.push(Scan {
+                        kind: Kind::Filter, context, relation, expr, rows,
+                    });
+                }
+                if let Some(expr) = sort {
+                    result.push(Scan {
+                        kind: Kind::Sort, context, relation, expr, rows,
+                    });
+                }
+            }
+        }
+    }
+    let sort_key = if node.node_type == "Sort" {
+        node.properties.get("sort_key").and_then(|v| v.as_array())
+            .and_then(|keys| keys.first())
+            .and_then(|key| key.as_str())
+    } else {
+        None
+    };
+    for child in &node.plans {
+        visit_debug(explain, child, context, sort_key, result);
+    }
+}
+
+fn visit_plan<'a>(explain: &Analysis, plan: &'a Plan,
+                  context: Option<Number>, result: &mut Vec<Scan<'a>>)
+{
+    let context = context_number(explain, &plan.contexts).or(context);
+    for stage in &plan.pipeline {
+        if stage.plan_type != "SeqScan" && stage.plan_type != "Seq Scan" {
+            continue;
+        }
+        let mut relation = None;
+        let mut filter = None;
+        for prop in &stage.properties {
+            match (&prop.title[..], &prop.value) {
+                ("relation_name", PropValue::Relation(name)) => {
+                    relation = Some(&name[..]);
+                }
+                ("filter", PropValue::Expr(expr)) => {
+                    filter = Some(&expr[..]);
+                }
+                _ => {}
+            }
+        }
+        let rows = stage.cost.plan_rows as f64;
+        if let (Some(relation), Some(expr)) = (relation, filter) {
+            if rows >= LARGE_RELATION_ROWS {
+                result.push(Scan {
+                    kind: Kind::Filter, context, relation, expr, rows,
+                });
+            }
+        }
+    }
+    for sub_plan in &plan.subplans {
+        visit_plan(explain, sub_plan, context, result);
+    }
+}
+
+fn context_number(explain: &Analysis, contexts: &[Context]) -> Option<Number>
+{
+    contexts.iter()
+        .flat_map(|ctx| explain.contexts.get(&ctx.context_id))
+        .next()
+        .copied()
+}
+
+fn context_text(explain: &Analysis, num: Number) -> Option<&str> {
+    for buffer in &explain.buffers {
+        for span in &buffer.contexts {
+            if span.num.0 == num.0 {
+                return buffer.text.get(span.offset..span.offset+span.len);
+            }
+        }
+    }
+    None
+}
+
+fn normalize_expr(expr: &str) -> String {
+    let mut expr = expr.split_whitespace().collect::<String>();
+    while expr.starts_with('(') && expr.ends_with(')') && expr.len() > 2 {
+        expr = expr[1..expr.len()-1].to_string();
+    }
+    expr
+}
+
+/// Finds `.name` in the expression, not matching longer names having the
+/// same prefix (e.g. `.id` in `.identifier`)
+fn find_pointer(expr: &str, name: &str) -> Option<usize> {
+    let needle = format!(".{name}");
+    expr.match_indices(&needle).map(|(pos, _)| pos).find(|pos| {
+        !expr[pos + needle.len()..].starts_with(|c: char| {
+            c.is_alphanumeric() || c == '_'
+        })
+    })
+}
+
+impl Schema {
+    async fn fetch(cli: &mut Connection) -> anyhow::Result<Schema> {
+        let pointers = cli.query::<Pointer, _>(r###"
+            WITH MODULE schema
+            FOR t IN (
+                SELECT ObjectType
+                FILTER NOT re_test(
+                    "^(?:std|schema|math|sys|cfg|cal|stdgraphql)::", .name)
+            ) UNION (
+                SELECT t.pointers {
+                    id,
+                    name,
+                    type_id := t.id,
+                    type_name := t.name,
+                }
+                FILTER .name != '__type__'
+            )
+        "###, &()).await?;
+        let mut schema = Schema {
+            types: BTreeMap::new(),
+            pointers: BTreeMap::new(),
+        };
+        for ptr in pointers {
+            schema.types.insert(ptr.type_id.to_string(), ptr.type_name.clone());
+            schema.types.insert(ptr.type_name.clone(), ptr.type_name.clone());
+            schema.pointers.entry(ptr.type_name)
+                .or_insert_with(Vec::new)
+                .push((ptr.id, ptr.name));
+        }
+        Ok(schema)
+    }
+    fn type_name(&self, relation: &str) -> Option<&String> {
+        let relation = relation.trim_matches('"');
+        if let Some(id) = UUID.find(relation) {
+            return self.types.get(id.as_str());
+        }
+        self.types.get(relation).or_else(|| {
+            // relation might be referred to by a short name
+            let suffix = format!("::{relation}");
+            self.types.values().find(|name| name.ends_with(&suffix))
+        })
+    }
+    fn index_for(&self, scan: &Scan) -> Option<(String, String)> {
+        let type_name = self.type_name(scan.relation)?;
+        let pointers = self.pointers.get(type_name)?;
+        let mut columns = pointers.iter()
+            .filter_map(|(id, name)| {
+                let pos = scan.expr.find(&id.to_string())
+                    .or_else(|| scan.expr.find(&format!("\"{name}\"")))
+                    .or_else(|| find_pointer(scan.expr, name))?;
+                Some((pos, name))
+            })
+            .collect::<Vec<_>>();
+        columns.sort();
+        match &columns[..] {
+            [] => None,
+            [(_, name)] => Some((type_name.clone(), format!(".{name}"))),
+            columns => {
+                let items = columns.iter()
+                    .map(|(_, name)| format!(".{name}"))
+                    .collect::<Vec<_>>();
+                Some((type_name.clone(), format!("({})", items.join(", "))))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::find_pointer;
+
+    #[test]
+    fn pointer() {
+        assert_eq!(find_pointer(".id = $0", "id"), Some(0));
+        assert_eq!(find_pointer("(.identifier = $0)", "id"), None);
+        assert_eq!(find_pointer(".identifier = .id", "id"), Some(14));
+        assert_eq!(find_pointer("lower(.name_2)", "name"), None);
+        assert_eq!(find_pointer("lower(.name)", "name"), Some(6));
+    }
+}
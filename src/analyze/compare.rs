@@ -0,0 +1,166 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+
+use crate::analyze::model::{Analysis, Cost, Shape};
+use crate::analyze::contexts::Number;
+use crate::analyze::table;
+use crate::print::{self, Highlight};
+
+
+#[derive(Debug, Clone, Copy)]
+struct Totals {
+    cost: f64,
+    time: Option<f64>,
+    rows: f64,
+}
+
+struct Change(Option<f64>, Option<f64>);
+struct Missing;
+
+impl Totals {
+    fn new(cost: &Cost) -> Totals {
+        let loops = cost.actual_loops.unwrap_or(1.);
+        Totals {
+            cost: cost.total_cost,
+            time: cost.actual_total_time.map(|t| t * loops),
+            rows: cost.actual_rows.unwrap_or(cost.plan_rows as f64),
+        }
+    }
+}
+
+fn collect(explain: &Analysis) -> (Option<Totals>, BTreeMap<u32, Totals>) {
+    let mut result = BTreeMap::new();
+    let Some(shape) = &explain.coarse_grained else {
+        return (None, result);
+    };
+    visit_shape(explain, shape, &mut result);
+    (Some(Totals::new(&shape.cost)), result)
+}
+
+fn visit_shape(explain: &Analysis, shape: &Shape,
+               result: &mut BTreeMap<u32, Totals>)
+{
+    let num = shape.contexts.iter()
+        .flat_map(|ctx| explain.contexts.get(&ctx.context_id))
+        .next();
+    if let Some(num) = num {
+        // outermost node wins, nested nodes in the same context are
+        // already accounted in its cost
+        result.entry(num.0).or_insert_with(|| Totals::new(&shape.cost));
+    }
+    for child in &shape.children {
+        visit_shape(explain, &child.node, result);
+    }
+}
+
+pub fn print(base: &Analysis, new: &Analysis) {
+    let base_query = base.buffers.get(0).map(|b| b.text.trim());
+    let new_query = new.buffers.get(0).map(|b| b.text.trim());
+    if base_query != new_query {
+        print::warn("Query text differs from the base analysis. \
+                     Contexts might not match.");
+    }
+    let (base_root, base_ctx) = collect(base);
+    let (new_root, new_ctx) = collect(new);
+    if base_root.is_none() && new_root.is_none() {
+        print::warn("No query plan to compare.");
+        return;
+    }
+    let execute = base.arguments.execute && new.arguments.execute;
+
+    let mut header = Vec::with_capacity(10);
+    header.push(Box::new("") as Box<dyn table::Contents>);
+    header.push(Box::new(table::Right("Base Cost".emphasize())));
+    header.push(Box::new(table::Right("Cost".emphasize())));
+    header.push(Box::new(table::Right("Change".emphasize())));
+    if execute {
+        header.push(Box::new(table::Right("Base Time".emphasize())));
+        header.push(Box::new(table::Right("Time".emphasize())));
+        header.push(Box::new(table::Right("Change".emphasize())));
+    }
+    header.push(Box::new(table::Right("Base Rows".emphasize())));
+    header.push(Box::new(table::Right("Rows".emphasize())));
+    header.push(Box::new(table::Right("Change".emphasize())));
+
+    let mut rows = vec![header];
+    rows.push(compare_row(
+        Box::new(format!("{}", "root".fade())), base_root, new_root, execute));
+    let numbers = base_ctx.keys().chain(new_ctx.keys())
+        .collect::<BTreeSet<_>>();
+    for num in numbers {
+        rows.push(compare_row(
+            Box::new(Number(*num)),
+            base_ctx.get(num).copied(),
+            new_ctx.get(num).copied(),
+            execute,
+        ));
+    }
+    table::render(Some("Plan Comparison"), &rows);
+}
+
+fn compare_row<'x>(
+    title: Box<dyn table::Contents + 'x>,
+    base: Option<Totals>,
+    new: Option<Totals>,
+    execute: bool,
+) -> Vec<Box<dyn table::Contents + 'x>> {
+    let mut row = Vec::with_capacity(10);
+    row.push(title);
+    let base_cost = base.map(|t| t.cost);
+    let new_cost = new.map(|t| t.cost);
+    value_column(&mut row, base_cost);
+    value_column(&mut row, new_cost);
+    row.push(Box::new(table::Right(Change(base_cost, new_cost))));
+    if execute {
+        let base_time = base.and_then(|t| t.time);
+        let new_time = new.and_then(|t| t.time);
+        value_column(&mut row, base_time);
+        value_column(&mut row, new_time);
+        row.push(Box::new(table::Right(Change(base_time, new_time))));
+    }
+    let base_rows = base.map(|t| t.rows);
+    let new_rows = new.map(|t| t.rows);
+    value_column(&mut row, base_rows);
+    value_column(&mut row, new_rows);
+    row.push(Box::new(table::Right(Change(base_rows, new_rows))));
+    row
+}
+
+fn value_column(row: &mut Vec<Box<dyn table::Contents + '_>>,
+                value: Option<f64>)
+{
+    match value {
+        Some(value) => row.push(Box::new(table::Float(value))),
+        None => row.push(Box::new(table::Right(Missing))),
+    }
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match (self.0, self.1) {
+            (Some(base), Some(new)) if base == new => write!(f, "="),
+            (Some(base), Some(new)) if base == 0. => {
+                let text = format!("+{:.1}", new);
+                write!(f, "{}", text.deleted())
+            }
+            (Some(base), Some(new)) => {
+                let percent = (new - base) / base * 100.;
+                let text = format!("{:+.1}%", percent);
+                if percent > 0. {
+                    write!(f, "{}", text.deleted())
+                } else {
+                    write!(f, "{}", text.added())
+                }
+            }
+            (None, Some(_)) => write!(f, "{}", "new".deleted()),
+            (Some(_), None) => write!(f, "{}", "gone".added()),
+            (None, None) => Missing.fmt(f),
+        }
+    }
+}
+
+impl fmt::Display for Missing {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "∅")
+    }
+}
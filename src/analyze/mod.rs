@@ -21,6 +21,9 @@ mod model;
 mod tree;
 mod table;
 mod contexts;
+mod compare;
+mod advise;
+mod report;
 
 pub use model::Analysis;
 
@@ -101,6 +104,9 @@ async fn is_special(path: &Path) -> anyhow::Result<bool> {
 pub async fn command(cli: &mut Connection, options: &Analyze)
     -> anyhow::Result<()>
 {
+    if let Some(path) = &options.file {
+        return report::analyze_file(cli, options, path).await;
+    }
     let data = if let Some(json_path) = &options.read_json {
         fs::read_to_string(&json_path).await
             .with_context(|| format!("cannot read {json_path:?}"))?
@@ -143,16 +149,32 @@ pub async fn command(cli: &mut Connection, options: &Analyze)
                 ))?;
         }
     } else {
-        let jd = &mut serde_json::Deserializer::from_str(&data);
-        let output = serde_path_to_error::deserialize(jd)
-            .with_context(|| format!("parsing explain output"))?;
-        let output = contexts::preprocess(output);
+        let output = parse_explain(&data)?;
 
         render_explain(&output)?;
         if options.expand {
             println!();
             render_expanded_explain(&output).await?;
         }
+        if let Some(base_path) = &options.compare {
+            let base_data = fs::read_to_string(&base_path).await
+                .with_context(|| format!("cannot read {base_path:?}"))?;
+            let base = parse_explain(&base_data)
+                .with_context(|| format!("cannot parse {base_path:?}"))?;
+            println!();
+            compare::print(&base, &output);
+        }
+        if options.advise {
+            println!();
+            advise::print(cli, &output).await?;
+        }
     }
     Ok(())
 }
+
+fn parse_explain(data: &str) -> anyhow::Result<Analysis> {
+    let jd = &mut serde_json::Deserializer::from_str(data);
+    let output = serde_path_to_error::deserialize(jd)
+        .with_context(|| format!("parsing explain output"))?;
+    Ok(contexts::preprocess(output))
+}
@@ -0,0 +1,203 @@
+use std::borrow::Cow;
+use std::io::{stdout, Write};
+use std::path::Path;
+
+use anyhow::Context;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use tokio::fs;
+
+use edgeql_parser::preparser::{full_statement, is_empty};
+
+use crate::analyze::{self, Analysis};
+use crate::analyze::table;
+use crate::classify;
+use crate::commands::ExitCode;
+use crate::commands::parser::{Analyze, ReportFormat};
+use crate::connect::Connection;
+use crate::print;
+
+
+static MAX_COST: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?m)^\s*#\s*max-cost:\s*([0-9]+(?:\.[0-9]*)?)\s*$").unwrap()
+});
+
+#[derive(Debug)]
+struct Statement<'a> {
+    text: &'a str,
+    line: usize,
+}
+
+#[derive(Debug, serde::Serialize)]
+struct Record {
+    index: usize,
+    line: usize,
+    query: String,
+    cost: f64,
+    time: Option<f64>,
+    max_cost: Option<f64>,
+    exceeded: bool,
+}
+
+pub async fn analyze_file(cli: &mut Connection, options: &Analyze,
+                          path: &Path)
+    -> anyhow::Result<()>
+{
+    let data = fs::read_to_string(path).await
+        .with_context(|| format!("cannot read {path:?}"))?;
+    let mut records = Vec::new();
+    for (idx, stmt) in split_statements(&data).into_iter().enumerate() {
+        let query = strip_comments(stmt.text);
+        let analyze_query = if classify::is_analyze(&query) {
+            Cow::Borrowed(&query[..])
+        } else {
+            Cow::Owned(format!("analyze {query}"))
+        };
+        let json = cli.query_required_single::<String, _>(&analyze_query, &())
+            .await
+            .with_context(|| format!(
+                "error analyzing query at {}:{}", path.display(), stmt.line))?;
+        let output = analyze::parse_explain(&json)?;
+        if options.report.is_none() {
+            table::print_title(
+                format_args!("{}:{}", path.display(), stmt.line), 80);
+            analyze::render_explain(&output)?;
+        }
+        let max_cost = MAX_COST.captures(stmt.text)
+            .and_then(|c| c.get(1))
+            .and_then(|m| m.as_str().parse::<f64>().ok());
+        let (cost, time) = totals(&output);
+        records.push(Record {
+            index: idx + 1,
+            line: stmt.line,
+            query,
+            cost,
+            time,
+            max_cost,
+            exceeded: max_cost.map(|max| cost > max).unwrap_or(false),
+        });
+    }
+    match options.report {
+        Some(ReportFormat::Json) => {
+            let mut out = serde_json::to_string_pretty(&records)?;
+            out.push('\n');
+            stdout().lock().write_all(out.as_bytes())?;
+        }
+        Some(ReportFormat::Csv) => {
+            let mut out = String::from(
+                "index,line,query,cost,time,max_cost,exceeded\n");
+            for rec in &records {
+                out.push_str(&format!("{},{},{},{},{},{},{}\n",
+                    rec.index, rec.line, csv_quote(&rec.query), rec.cost,
+                    rec.time.map(|t| t.to_string()).unwrap_or_default(),
+                    rec.max_cost.map(|t| t.to_string()).unwrap_or_default(),
+                    rec.exceeded));
+            }
+            stdout().lock().write_all(out.as_bytes())?;
+        }
+        None => {}
+    }
+    let mut failed = false;
+    for rec in records.iter().filter(|r| r.exceeded) {
+        print::error(format!(
+            "query at {}:{} has cost {:.1} which exceeds max-cost {:.1}",
+            path.display(), rec.line,
+            rec.cost, rec.max_cost.unwrap_or(0.)));
+        failed = true;
+    }
+    if failed {
+        return Err(ExitCode::new(1).into());
+    }
+    Ok(())
+}
+
+fn totals(explain: &Analysis) -> (f64, Option<f64>) {
+    let cost = if let Some(shape) = &explain.coarse_grained {
+        &shape.cost
+    } else if let Some(node) = explain.debug_info.as_ref()
+        .and_then(|d| d.full_plan.as_ref())
+    {
+        &node.cost
+    } else {
+        return (0., None);
+    };
+    let loops = cost.actual_loops.unwrap_or(1.);
+    (cost.total_cost, cost.actual_total_time.map(|t| t * loops))
+}
+
+fn split_statements(data: &str) -> Vec<Statement> {
+    let mut result = Vec::new();
+    let mut offset = 0;
+    while offset < data.len() {
+        let tail = &data[offset..];
+        let len = full_statement(tail.as_bytes(), None)
+            .unwrap_or(tail.len());
+        let text = &tail[..len];
+        if !is_empty(text) {
+            result.push(Statement {
+                text,
+                line: first_line(data, offset),
+            });
+        }
+        offset += len;
+    }
+    result
+}
+
+/// Line number (1-based) of the first line containing code
+fn first_line(data: &str, offset: usize) -> usize {
+    let mut line = data[..offset].matches('\n').count() + 1;
+    for text in data[offset..].lines() {
+        let text = text.trim();
+        if !text.is_empty() && !text.starts_with('#') {
+            break;
+        }
+        line += 1;
+    }
+    line
+}
+
+fn strip_comments(text: &str) -> String {
+    text.lines()
+        .filter(|line| !line.trim_start().starts_with('#'))
+        .collect::<Vec<_>>()
+        .join("\n")
+        .trim()
+        .to_string()
+}
+
+fn csv_quote(value: &str) -> Cow<str> {
+    if value.contains(&[',', '"', '\n', '\r'][..]) {
+        Cow::Owned(format!("\"{}\"", value.replace('"', "\"\"")))
+    } else {
+        Cow::Borrowed(value)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{split_statements, MAX_COST};
+
+    #[test]
+    fn statements_and_thresholds() {
+        let data = "\
+            # max-cost: 100\n\
+            select 1;\n\
+            \n\
+            # just a comment\n\
+            select User { name }\n\
+                filter .name = 'x';\n\
+            # max-cost: 12.5\n\
+            select 2\n";
+        let stmts = split_statements(data);
+        assert_eq!(stmts.len(), 3);
+        assert_eq!(stmts[0].line, 2);
+        assert_eq!(stmts[1].line, 5);
+        assert_eq!(stmts[2].line, 8);
+        let costs = stmts.iter()
+            .map(|s| MAX_COST.captures(s.text)
+                 .map(|c| c[1].to_string()))
+            .collect::<Vec<_>>();
+        assert_eq!(costs, [Some("100".into()), None, Some("12.5".into())]);
+    }
+}
@@ -49,6 +49,16 @@ Introspection
   \la [-vsc] [PATTERN]      List expression aliases (alias: \list aliases)
   \lc [-c]   [PATTERN]      List casts              (alias: \list casts)
   \li [-vsc] [PATTERN]      List indexes            (alias: \list indexes)
+  \lf [-sc]  [PATTERN]      List functions          (alias: \list functions)
+  \lo [-sc]  [PATTERN]      List operators          (alias: \list operators)
+  \lC [-sc]  [PATTERN]      List constraints        (alias: \list constraints)
+  \lg [-c]   [PATTERN]      List globals            (alias: \list globals)
+  \lx [-c]   [PATTERN]      List extensions         (alias: \list extensions)
+  \lp [-c]   [PATTERN]      List access policies    (alias: \list policies)
+  \list triggers [-c] [PATTERN]
+                            List triggers
+  \list rewrites [-c] [PATTERN]
+                            List mutation rewrites
 
 Operations
   \dump FILENAME            Create dump of current database as a file
@@ -313,6 +323,12 @@ impl CommandCache {
         aliases.insert("la", &["list", "aliases"]);
         aliases.insert("lc", &["list", "casts"]);
         aliases.insert("li", &["list", "indexes"]);
+        aliases.insert("lf", &["list", "functions"]);
+        aliases.insert("lo", &["list", "operators"]);
+        aliases.insert("lC", &["list", "constraints"]);
+        aliases.insert("lg", &["list", "globals"]);
+        aliases.insert("lx", &["list", "extensions"]);
+        aliases.insert("lp", &["list", "policies"]);
         aliases.insert("s", &["history"]);
         aliases.insert("e", &["edit"]);
         aliases.insert("c", &["connect"]);
@@ -569,6 +585,9 @@ pub async fn execute(cmd: &BackslashCmd, prompt: &mut repl::State)
             let cli = prompt.connection.as_mut()
                 .expect("connection established");
             execute::common(cli, cmd, &options).await?;
+            // command might have changed session state
+            // (e.g. `\configure --scope=session`)
+            prompt.read_state();
             Ok(Skip)
         }
         Set(SetCommand {setting: None}) => {
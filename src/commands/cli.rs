@@ -3,7 +3,7 @@ use is_terminal::IsTerminal;
 use crate::cli::directory_check;
 use crate::cli;
 use crate::cloud::main::cloud_main;
-use crate::commands::parser::Common;
+use crate::commands::parser::{Common, Dump, DumpCmd};
 use crate::commands;
 use crate::migrations::options::{MigrationCmd as M, Migration};
 use crate::migrations;
@@ -54,6 +54,11 @@ pub fn main(options: &Options) -> Result<(), anyhow::Error> {
                 ) => {
                     migrations::upgrade_check(&cmdopt, params)
                 }
+                Common::Dump(Dump { subcommand: Some(cmd), .. })
+                    if !matches!(cmd, DumpCmd::ExtractSchema(c) if c.sdl)
+                => {
+                    commands::dump_file(cmd)
+                }
                 // Otherwise connect
                 cmd => common_cmd(options, cmdopt, cmd),
             }
@@ -0,0 +1,209 @@
+use anyhow::Context;
+use is_terminal::IsTerminal;
+use prettytable::{Table, Row, Cell};
+
+use crate::commands::config_file::{self, Auth, fetch_auth};
+use crate::commands::list;
+use crate::commands::parser::{AuthCmd, AuthEdit, AuthRemove};
+use crate::commands::{ExitCode, Options};
+use crate::connect::Connection;
+use crate::hint::HintExt;
+use crate::portable::exit_codes;
+use crate::print;
+use crate::question;
+use crate::table;
+
+
+/// Authentication methods supported by the server
+#[derive(serde::Deserialize, Debug)]
+pub struct Methods {
+    /// Short names of methods (e.g. `SCRAM`, `JWT`, `mTLS`)
+    pub names: Vec<String>,
+    /// Whether methods can be restricted to specific transports
+    pub transports: bool,
+}
+
+pub async fn fetch_methods(cli: &mut Connection) -> anyhow::Result<Methods> {
+    let data = cli.query_required_single::<String, _>(r###"
+        WITH MODULE schema
+        SELECT to_str(<json>(
+            names := array_agg((
+                SELECT ObjectType
+                FILTER 'cfg::AuthMethod' IN .ancestors.name
+                    AND NOT .abstract
+            ).name),
+            transports := EXISTS (
+                SELECT (
+                    SELECT ObjectType FILTER .name = 'cfg::AuthMethod'
+                ).properties
+                FILTER .name = 'transports'
+            ),
+        ))
+    "###, &()).await?;
+    let mut methods: Methods = serde_json::from_str(&data)
+        .context("cannot decode authentication methods")?;
+    for name in &mut methods.names {
+        if let Some(short) = name.strip_prefix("cfg::") {
+            *name = short.into();
+        }
+    }
+    methods.names.sort();
+    Ok(methods)
+}
+
+impl Methods {
+    /// Returns name of the method as defined in the schema, method names
+    /// are matched case-insensitively (i.e. `scram` matches `SCRAM`)
+    pub fn canonical(&self, name: &str) -> anyhow::Result<&str> {
+        let name = name.trim_start_matches("cfg::");
+        self.names.iter()
+            .find(|m| m.eq_ignore_ascii_case(name))
+            .map(|m| &m[..])
+            .ok_or_else(|| anyhow::anyhow!(
+                "unknown authentication method {:?}", name))
+            .with_hint(|| format!("Supported methods: {}",
+                                  self.names.join(", ")))
+            .map_err(Into::into)
+    }
+    pub fn check_transports(&self, transports: &[String])
+        -> anyhow::Result<()>
+    {
+        if !transports.is_empty() && !self.transports {
+            return Err(anyhow::anyhow!(
+                "transports are not supported by this server"
+            )).hint("Transports are supported since EdgeDB 4.0")?;
+        }
+        Ok(())
+    }
+}
+
+pub async fn command(cli: &mut Connection, options: &Options, cmd: &AuthCmd)
+    -> anyhow::Result<()>
+{
+    match cmd {
+        AuthCmd::List(c) => list_rules(cli, options, c.json).await,
+        AuthCmd::Remove(c) => remove(cli, c).await,
+        AuthCmd::Edit(c) => edit(cli, c).await,
+    }
+}
+
+async fn list_rules(cli: &mut Connection, options: &Options, json: bool)
+    -> anyhow::Result<()>
+{
+    let mut rules = fetch_auth(cli).await?;
+    rules.sort_by_key(|r| r.priority);
+    if json {
+        return list::print_json(&rules);
+    }
+    if !options.command_line || std::io::stdout().is_terminal() {
+        let mut table = Table::new();
+        table.set_format(*table::FORMAT);
+        table.set_titles(Row::new(
+            ["Priority", "Users", "Method", "Transports", "Comment"]
+            .iter().map(|x| table::header_cell(x)).collect()));
+        for rule in &rules {
+            table.add_row(Row::new(vec![
+                Cell::new(&rule.priority.to_string()),
+                Cell::new(&rule.user.join(", ")),
+                Cell::new(&rule.method),
+                Cell::new(&rule.transports.join(", ")),
+                Cell::new(rule.comment.as_deref().unwrap_or("")),
+            ]));
+        }
+        if table.is_empty() {
+            eprintln!("No authentication rules configured.");
+        } else {
+            table.printstd();
+        }
+    } else {
+        for rule in &rules {
+            println!("{}\t{}\t{}\t{}\t{}",
+                rule.priority, rule.user.join(","), rule.method,
+                rule.transports.join(","),
+                rule.comment.as_deref().unwrap_or(""));
+        }
+    }
+    Ok(())
+}
+
+async fn find_rule(cli: &mut Connection, priority: i64)
+    -> anyhow::Result<Auth>
+{
+    fetch_auth(cli).await?
+        .into_iter()
+        .find(|r| r.priority == priority)
+        .ok_or_else(|| anyhow::anyhow!(
+            "no authentication rule with priority {}", priority))
+        .hint("Run `edgedb configure auth list` to see configured rules")
+        .map_err(Into::into)
+}
+
+async fn remove(cli: &mut Connection, cmd: &AuthRemove)
+    -> anyhow::Result<()>
+{
+    let rule = find_rule(cli, cmd.priority).await?;
+    if !cmd.non_interactive {
+        let users = if rule.user.is_empty() {
+            String::from("*")
+        } else {
+            rule.user.join(", ")
+        };
+        let q = question::Confirm::new_dangerous(format!(
+            "Do you really want to remove authentication rule \
+             with priority {} ({} for {})?",
+            rule.priority, rule.method, users,
+        ));
+        if !cli.ping_while(q.async_ask()).await? {
+            print::error("Canceled.");
+            return Err(ExitCode::new(exit_codes::NOT_CONFIRMED).into());
+        }
+    }
+    print::completion(&cli.execute(
+        &format!("CONFIGURE INSTANCE RESET Auth FILTER .priority = {}",
+                 rule.priority),
+        &(),
+    ).await?);
+    Ok(())
+}
+
+async fn edit(cli: &mut Connection, cmd: &AuthEdit) -> anyhow::Result<()> {
+    let methods = fetch_methods(cli).await?;
+    let old = find_rule(cli, cmd.priority).await?;
+    let mut rule = old.clone();
+    if let Some(priority) = cmd.new_priority {
+        rule.priority = priority;
+    }
+    if !cmd.users.is_empty() {
+        rule.user = cmd.users.clone();
+    }
+    if let Some(method) = &cmd.method {
+        rule.method = methods.canonical(method)?.into();
+    }
+    if !cmd.transports.is_empty() {
+        methods.check_transports(&cmd.transports)?;
+        rule.transports = cmd.transports.clone();
+    }
+    if let Some(comment) = &cmd.comment {
+        rule.comment = Some(comment.clone()).filter(|c| !c.is_empty());
+    }
+    if rule == old {
+        print::warn("Nothing to change.");
+        return Ok(());
+    }
+    cli.execute(
+        &format!("CONFIGURE INSTANCE RESET Auth FILTER .priority = {}",
+                 old.priority),
+        &(),
+    ).await?;
+    if let Err(e) = cli.execute(&config_file::insert_auth("INSTANCE", &rule),
+                                &()).await
+    {
+        // instance configuration is not transactional, put the old rule back
+        cli.execute(&config_file::insert_auth("INSTANCE", &old), &()).await
+            .context("cannot restore the original rule")?;
+        return Err(e.into());
+    }
+    print::success(format!("Authentication rule with priority {} updated.",
+                           rule.priority));
+    Ok(())
+}
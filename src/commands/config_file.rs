@@ -0,0 +1,462 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use anyhow::Context;
+use edgeql_parser::helpers::quote_string;
+
+use crate::commands::config_auth;
+use crate::commands::config_schema::{self, Setting};
+use crate::connect::Connection;
+use crate::print;
+
+
+/// Configuration file as written by `configure export`. A section missing
+/// from the file is left intact by `configure apply`
+#[derive(serde::Serialize, serde::Deserialize, Debug, Default)]
+#[serde(deny_unknown_fields)]
+struct ConfigFile {
+    #[serde(default, skip_serializing_if="Option::is_none")]
+    instance: Option<Section>,
+    #[serde(default, skip_serializing_if="Option::is_none")]
+    database: Option<Section>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Default)]
+struct Section {
+    #[serde(flatten)]
+    settings: BTreeMap<String, toml::Value>,
+    #[serde(rename="Auth", default, skip_serializing_if="Vec::is_empty")]
+    auth: Vec<Auth>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+#[derive(PartialEq, Eq, PartialOrd, Ord)]
+pub struct Auth {
+    pub priority: i64,
+    #[serde(default)]
+    pub user: Vec<String>,
+    pub method: String,
+    #[serde(default, skip_serializing_if="Vec::is_empty")]
+    pub transports: Vec<String>,
+    #[serde(default, skip_serializing_if="Option::is_none")]
+    pub comment: Option<String>,
+}
+
+#[derive(Debug)]
+enum Scope {
+    Instance,
+    Database,
+}
+
+pub async fn export(cli: &mut Connection) -> anyhow::Result<()> {
+    let settings = config_schema::fetch_settings(cli).await?;
+    let mut instance = Section::default();
+    let mut database = Section::default();
+    for setting in &settings {
+        let values = config_schema::fetch_values(cli, setting).await?;
+        if let Some(value) = values.instance_explicit() {
+            if let Some(value) = to_toml(value) {
+                instance.settings.insert(setting.name.clone(), value);
+            }
+        }
+        if let Some(value) = values.database_explicit() {
+            if let Some(value) = to_toml(value) {
+                database.settings.insert(setting.name.clone(), value);
+            }
+        }
+    }
+    instance.auth = fetch_auth(cli).await?;
+    let file = ConfigFile {
+        instance: Some(instance),
+        database: Some(database),
+    };
+    print!("{}", toml::to_string(&file)?);
+    Ok(())
+}
+
+/// Statement changing instance configuration and the one reverting it
+#[derive(Debug)]
+struct Change {
+    statement: String,
+    undo: String,
+}
+
+/// Instance configuration can't be changed in a transaction, so instance
+/// changes are reverted one by one when a later statement fails. Database
+/// changes are applied in a transaction
+#[derive(Debug, Default)]
+struct Plan {
+    instance: Vec<Change>,
+    database: Vec<String>,
+}
+
+pub async fn apply(cli: &mut Connection, path: &Path, dry_run: bool)
+    -> anyhow::Result<()>
+{
+    let data = fs_err::read_to_string(path)?;
+    let file: ConfigFile = toml::from_str(&data)
+        .with_context(|| format!("cannot parse {}", path.display()))?;
+    if file.database.as_ref().map(|s| !s.auth.is_empty()).unwrap_or(false) {
+        anyhow::bail!("`Auth` can only be configured for the instance");
+    }
+    let settings = config_schema::fetch_settings(cli).await?;
+    for section in file.instance.iter().chain(file.database.iter()) {
+        for name in section.settings.keys() {
+            config_schema::find(&settings, name)?;
+        }
+    }
+
+    // all values are validated by the server before anything is changed
+    let mut plan = Plan::default();
+    for setting in &settings {
+        let values = config_schema::fetch_values(cli, setting).await?;
+        let mut instance_changed = false;
+        if let Some(instance) = &file.instance {
+            let change = diff(cli, Scope::Instance, setting,
+                              values.instance_explicit(),
+                              Some(&values.instance),
+                              instance.settings.get(&setting.name)).await?;
+            if let Some(change) = change {
+                instance_changed = true;
+                plan.instance.push(change);
+            }
+        }
+        if let Some(database) = &file.database {
+            let desired = database.settings.get(&setting.name);
+            if desired.is_some() && setting.system {
+                anyhow::bail!("setting {:?} can only be configured \
+                               for the instance", setting.name);
+            }
+            // inherited value will change with the instance value
+            let actual = if instance_changed {
+                values.database_explicit()
+            } else {
+                Some(&values.database)
+            };
+            let change = diff(cli, Scope::Database, setting,
+                              values.database_explicit(), actual,
+                              desired).await?;
+            if let Some(change) = change {
+                plan.database.push(change.statement);
+            }
+        }
+    }
+    if let Some(instance) = file.instance {
+        let methods = config_auth::fetch_methods(cli).await?;
+        let mut desired_auth = instance.auth;
+        for item in &mut desired_auth {
+            item.method = methods.canonical(&item.method)
+                .with_context(|| format!("invalid Auth with priority {}",
+                                         item.priority))?
+                .into();
+            methods.check_transports(&item.transports)?;
+            normalize_auth(item);
+        }
+        diff_auth(&mut plan.instance, fetch_auth(cli).await?, desired_auth);
+    }
+
+    if plan.instance.is_empty() && plan.database.is_empty() {
+        print::success("Configuration is up to date.");
+        return Ok(());
+    }
+    for change in &plan.instance {
+        println!("{};", change.statement);
+    }
+    for statement in &plan.database {
+        println!("{};", statement);
+    }
+    if dry_run {
+        return Ok(());
+    }
+    for (idx, change) in plan.instance.iter().enumerate() {
+        if let Err(e) = cli.execute(&change.statement, &()).await {
+            revert(cli, &plan.instance[..idx]).await;
+            return Err(e).with_context(|| {
+                format!("cannot apply `{}`", change.statement)
+            });
+        }
+    }
+    if let Err(e) = apply_database(cli, &plan.database).await {
+        revert(cli, &plan.instance).await;
+        return Err(e);
+    }
+    print::success("Configuration applied.");
+    Ok(())
+}
+
+async fn apply_database(cli: &mut Connection, statements: &[String])
+    -> anyhow::Result<()>
+{
+    if statements.is_empty() {
+        return Ok(());
+    }
+    cli.execute("START TRANSACTION", &()).await?;
+    for statement in statements {
+        if let Err(e) = cli.execute(statement, &()).await {
+            cli.execute("ROLLBACK", &()).await
+                .map_err(|e| {
+                    log::warn!("Error rolling back the transaction: {:#}", e)
+                })
+                .ok();
+            return Err(e).with_context(|| {
+                format!("cannot apply `{}`", statement)
+            });
+        }
+    }
+    cli.execute("COMMIT", &()).await?;
+    Ok(())
+}
+
+/// Reverts applied instance changes in reverse order
+async fn revert(cli: &mut Connection, applied: &[Change]) {
+    for change in applied.iter().rev() {
+        if let Err(e) = cli.execute(&change.undo, &()).await {
+            print::error(format!("Cannot revert `{}`: {:#}",
+                                 change.statement, e));
+        }
+    }
+}
+
+/// Returns the change needed to make `desired` the value of the setting.
+/// `explicit` is the value configured in the scope, `actual` is the value
+/// in the scope including the inherited one
+async fn diff(cli: &mut Connection, scope: Scope, setting: &Setting,
+              explicit: Option<&serde_json::Value>,
+              actual: Option<&serde_json::Value>,
+              desired: Option<&toml::Value>)
+    -> anyhow::Result<Option<Change>>
+{
+    let keyword = match scope {
+        Scope::Instance => "INSTANCE",
+        Scope::Database => "CURRENT DATABASE",
+    };
+    let reset = format!("CONFIGURE {} RESET {}", keyword, setting.name);
+    let restore = match explicit {
+        Some(value) => set_statement(keyword, setting, value)?,
+        None => reset.clone(),
+    };
+    match (explicit, desired) {
+        (None, None) => Ok(None),
+        (Some(_), None) => {
+            Ok(Some(Change { statement: reset, undo: restore }))
+        }
+        (_, Some(desired)) => {
+            let values = from_toml(setting, desired)?;
+            let normalized = config_schema::normalize(
+                cli, setting, &values).await?;
+            if actual == Some(&normalized) {
+                return Ok(None);
+            }
+            Ok(Some(Change {
+                statement: format!("CONFIGURE {} SET {} := {}",
+                                   keyword, setting.name,
+                                   setting.value_expr(&values)?),
+                undo: restore,
+            }))
+        }
+    }
+}
+
+/// Statement setting a value as returned by `config_schema::fetch_values`
+fn set_statement(keyword: &str, setting: &Setting, value: &serde_json::Value)
+    -> anyhow::Result<String>
+{
+    use serde_json::Value;
+
+    let string = |value: &Value| match value {
+        Value::String(s) => s.clone(),
+        value => value.to_string(),
+    };
+    let values = match value {
+        Value::Null => {
+            return Ok(format!("CONFIGURE {} RESET {}",
+                              keyword, setting.name));
+        }
+        Value::Array(items) => items.iter().map(string).collect(),
+        value => vec![string(value)],
+    };
+    Ok(format!("CONFIGURE {} SET {} := {}",
+               keyword, setting.name, setting.value_expr(&values)?))
+}
+
+fn diff_auth(plan: &mut Vec<Change>, current: Vec<Auth>, desired: Vec<Auth>)
+{
+    let reset = |item: &Auth| {
+        format!("CONFIGURE INSTANCE RESET Auth FILTER .priority = {}",
+                item.priority)
+    };
+    for item in &current {
+        if !desired.contains(item) {
+            plan.push(Change {
+                statement: reset(item),
+                undo: insert_auth("INSTANCE", item),
+            });
+        }
+    }
+    for item in &desired {
+        if !current.contains(item) {
+            plan.push(Change {
+                statement: insert_auth("INSTANCE", item),
+                undo: reset(item),
+            });
+        }
+    }
+}
+
+pub fn insert_auth(scope: &str, item: &Auth) -> String {
+    let method = item.method.trim_start_matches("cfg::");
+    let method = if item.transports.is_empty() {
+        format!("(INSERT cfg::{})", method)
+    } else {
+        let transports = item.transports.iter()
+            .map(|x| format!("<cfg::ConnectionTransport>{}", quote_string(x)))
+            .collect::<Vec<_>>().join(", ");
+        format!("(INSERT cfg::{} {{ transports := {{ {} }} }})",
+                method, transports)
+    };
+    let mut props = vec![
+        format!("priority := {}", item.priority),
+        format!("method := {}", method),
+    ];
+    if !item.user.is_empty() {
+        let users = item.user.iter().map(|x| quote_string(x))
+            .collect::<Vec<_>>().join(", ");
+        props.push(format!("user := {{ {} }}", users));
+    }
+    if let Some(comment) = &item.comment {
+        props.push(format!("comment := {}", quote_string(comment)));
+    }
+    format!("CONFIGURE {} INSERT Auth {{ {} }}", scope, props.join(", "))
+}
+
+pub async fn fetch_auth(cli: &mut Connection) -> anyhow::Result<Vec<Auth>> {
+    let methods = config_auth::fetch_methods(cli).await?;
+    let data = cli.query_required_single::<String, _>(&format!(r###"
+        SELECT to_str(<json>array_agg(cfg::InstanceConfig.auth {{
+            priority,
+            user,
+            comment,
+            method := .method.__type__.name,
+            {transports}
+        }}))
+    "###,
+        // transports are only supported by EdgeDB >= 4.0
+        transports=if methods.transports {
+            "transports := .method.transports,"
+        } else {
+            ""
+        },
+    ), &()).await?;
+    let mut items: Vec<Auth> = serde_json::from_str(&data)
+        .context("cannot decode auth configuration")?;
+    for item in &mut items {
+        normalize_auth(item);
+    }
+    items.sort();
+    Ok(items)
+}
+
+fn normalize_auth(item: &mut Auth) {
+    item.user.sort();
+    item.transports.sort();
+    if let Some(name) = item.method.strip_prefix("cfg::") {
+        item.method = name.into();
+    }
+}
+
+fn to_toml(value: &serde_json::Value) -> Option<toml::Value> {
+    use serde_json::Value;
+
+    match value {
+        Value::Null => None,
+        Value::String(s) => Some(toml::Value::String(s.clone())),
+        Value::Array(items) => {
+            let items = items.iter().filter_map(to_toml).collect();
+            Some(toml::Value::Array(items))
+        }
+        value => Some(toml::Value::String(value.to_string())),
+    }
+}
+
+fn from_toml(setting: &Setting, value: &toml::Value)
+    -> anyhow::Result<Vec<String>>
+{
+    use toml::Value;
+
+    let scalar = |value: &Value| match value {
+        Value::String(s) => Ok(s.clone()),
+        Value::Integer(v) => Ok(v.to_string()),
+        Value::Float(v) => Ok(v.to_string()),
+        Value::Boolean(v) => Ok(v.to_string()),
+        _ => Err(anyhow::anyhow!("unsupported value for {:?}: {}",
+                                 setting.name, value)),
+    };
+    match value {
+        Value::Array(items) if setting.multi => {
+            items.iter().map(scalar).collect()
+        }
+        value => Ok(vec![scalar(value)?]),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Auth, ConfigFile, diff_auth};
+
+    #[test]
+    fn parse_file() {
+        let file: ConfigFile = toml::from_str(r#"
+            [instance]
+            query_execution_timeout = "PT10S"
+            listen_addresses = ["127.0.0.1", "::1"]
+
+            [[instance.Auth]]
+            priority = 0
+            user = ["admin"]
+            method = "SCRAM"
+
+            [database]
+            "ext::pgvector::Config::probes" = 10
+        "#).unwrap();
+        let instance = file.instance.unwrap();
+        assert_eq!(instance.settings.len(), 2);
+        assert_eq!(instance.auth.len(), 1);
+        assert_eq!(file.database.unwrap().settings.len(), 1);
+
+        let file: ConfigFile = toml::from_str(r#"
+            [database]
+            query_execution_timeout = "PT10S"
+        "#).unwrap();
+        assert!(file.instance.is_none());
+        assert!(file.database.is_some());
+    }
+
+    #[test]
+    fn auth_diff() {
+        let entry = |priority, method: &str| Auth {
+            priority,
+            user: vec!["*".into()],
+            method: method.into(),
+            transports: Vec::new(),
+            comment: None,
+        };
+        let mut plan = Vec::new();
+        diff_auth(&mut plan,
+            vec![entry(0, "Trust"), entry(1, "SCRAM")],
+            vec![entry(0, "SCRAM"), entry(1, "SCRAM")]);
+        let statements = plan.iter()
+            .map(|c| &c.statement[..])
+            .collect::<Vec<_>>();
+        assert_eq!(statements, [
+            "CONFIGURE INSTANCE RESET Auth FILTER .priority = 0",
+            "CONFIGURE INSTANCE INSERT Auth { priority := 0, \
+             method := (INSERT cfg::SCRAM), user := { '*' } }",
+        ]);
+        // removed rule is restored on failure
+        assert_eq!(plan[0].undo,
+            "CONFIGURE INSTANCE INSERT Auth { priority := 0, \
+             method := (INSERT cfg::Trust), user := { '*' } }");
+        assert_eq!(plan[1].undo,
+            "CONFIGURE INSTANCE RESET Auth FILTER .priority = 0");
+    }
+}
@@ -0,0 +1,264 @@
+use anyhow::Context;
+use edgedb_derive::Queryable;
+use edgeql_parser::helpers::{quote_name, quote_string};
+
+use crate::commands::helpers::quote_namespaced;
+use crate::connect::Connection;
+use crate::hint::HintExt;
+
+
+#[derive(Queryable)]
+struct SettingRow {
+    type_name: String,
+    name: String,
+    target_name: String,
+    multi: bool,
+    system: bool,
+    default_expr: String,
+}
+
+/// Configuration setting as discovered from the `cfg` schema
+#[derive(Debug, Clone)]
+pub struct Setting {
+    /// Name as used in `CONFIGURE ... SET <name>`, extension settings are
+    /// prefixed by the config type (e.g. `ext::pgvector::Config::probes`)
+    pub name: String,
+    /// Object type that holds the property (`cfg::AbstractConfig` or
+    /// extension config type)
+    pub type_name: String,
+    pub property: String,
+    pub target: String,
+    pub multi: bool,
+    /// Setting can only be changed for the whole instance
+    pub system: bool,
+    pub default: Option<String>,
+}
+
+pub async fn fetch_settings(cli: &mut Connection)
+    -> anyhow::Result<Vec<Setting>>
+{
+    let rows = cli.query::<SettingRow, _>(r###"
+        WITH MODULE schema
+        FOR t IN (
+            SELECT ObjectType
+            FILTER .name = 'cfg::AbstractConfig'
+                OR 'cfg::ExtensionConfig' IN .ancestors.name
+        ) UNION (
+            SELECT t.properties {
+                type_name := t.name,
+                name,
+                target_name := .target.name,
+                multi := (<str>.cardinality ?? '') = 'Many',
+                system := EXISTS (
+                    SELECT .annotations
+                    FILTER .name = 'cfg::system' AND @value = 'true'
+                ),
+                default_expr := .default ?? '',
+            }
+            FILTER .name NOT LIKE '\\_%' AND .name != 'id'
+                AND .name != 'force_database_error'
+                AND NOT EXISTS (
+                    SELECT .annotations
+                    FILTER .name = 'cfg::internal' AND @value = 'true'
+                )
+        )
+    "###, &()).await.context("cannot fetch configuration schema")?;
+    let mut settings = rows.into_iter().map(|row| {
+        Setting {
+            name: setting_name(&row.type_name, &row.name),
+            type_name: row.type_name,
+            property: row.name,
+            target: row.target_name,
+            multi: row.multi,
+            system: row.system,
+            default: Some(row.default_expr).filter(|x| !x.is_empty()),
+        }
+    }).collect::<Vec<_>>();
+    settings.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(settings)
+}
+
+fn setting_name(type_name: &str, property: &str) -> String {
+    if type_name == "cfg::AbstractConfig" {
+        property.into()
+    } else {
+        format!("{}::{}", type_name, property)
+    }
+}
+
+pub fn find<'x>(settings: &'x [Setting], name: &str)
+    -> anyhow::Result<&'x Setting>
+{
+    settings.iter().find(|s| s.name == name)
+        .ok_or_else(|| anyhow::anyhow!(
+            "unknown configuration setting {:?}", name))
+        .hint("Run `edgedb configure show` to list available settings")
+        .map_err(Into::into)
+}
+
+impl Setting {
+    /// Expression to be used as the value of the setting, each value is
+    /// cast from string to the type of the setting
+    pub fn value_expr(&self, values: &[String]) -> anyhow::Result<String> {
+        if !self.multi && values.len() != 1 {
+            anyhow::bail!("setting {:?} requires exactly one value",
+                          self.name);
+        }
+        let items = values.iter()
+            .map(|v| format!("<{}>{}",
+                             quote_namespaced(&self.target), quote_string(v)))
+            .collect::<Vec<_>>();
+        if self.multi {
+            Ok(format!("{{{}}}", items.join(", ")))
+        } else {
+            Ok(items.into_iter().next().unwrap())
+        }
+    }
+    /// Parses JSON array of values, for single settings the value is
+    /// unwrapped (`null` if not set)
+    fn parse_json(&self, data: &str) -> anyhow::Result<serde_json::Value> {
+        let value: serde_json::Value = serde_json::from_str(data)?;
+        if self.multi {
+            return Ok(value);
+        }
+        match value {
+            serde_json::Value::Array(mut items) if items.len() <= 1 => {
+                Ok(items.pop().unwrap_or(serde_json::Value::Null))
+            }
+            value => Ok(value),
+        }
+    }
+    /// Path to the setting's value starting from one of the config
+    /// objects (`cfg::Config`, `cfg::DatabaseConfig`, `cfg::InstanceConfig`)
+    pub fn path(&self, config: &str) -> String {
+        if self.type_name == "cfg::AbstractConfig" {
+            format!("{}.{}", config, quote_name(&self.property))
+        } else {
+            format!("{}.extensions[IS {}].{}", config,
+                    quote_namespaced(&self.type_name),
+                    quote_name(&self.property))
+        }
+    }
+}
+
+pub struct Values {
+    pub effective: serde_json::Value,
+    pub database: serde_json::Value,
+    pub instance: serde_json::Value,
+    pub default: serde_json::Value,
+}
+
+impl Values {
+    /// Value set explicitly for the instance
+    pub fn instance_explicit(&self) -> Option<&serde_json::Value> {
+        Some(&self.instance).filter(|v| *v != &self.default)
+    }
+    /// Value set explicitly for the current database
+    pub fn database_explicit(&self) -> Option<&serde_json::Value> {
+        Some(&self.database).filter(|v| *v != &self.instance)
+    }
+    pub fn source(&self) -> &'static str {
+        if self.effective != self.database {
+            "session"
+        } else if self.database != self.instance {
+            "database"
+        } else if self.instance != self.default {
+            "instance"
+        } else {
+            "default"
+        }
+    }
+}
+
+pub async fn fetch_values(cli: &mut Connection, setting: &Setting)
+    -> anyhow::Result<Values>
+{
+    // values are cast to str so that every scalar (including
+    // `cfg::memory` and enums) can be represented in JSON
+    let default = match &setting.default {
+        Some(expr) => format!("({})", expr),
+        None => String::from("<str>{}"),
+    };
+    let (effective, database, instance, default) = cli
+        .query_required_single::<(String, String, String, String), _>(
+        &format!(r###"
+            SELECT (
+                to_str(<json>array_agg(<str>{effective})),
+                to_str(<json>array_agg(<str>{database})),
+                to_str(<json>array_agg(<str>{instance})),
+                to_str(<json>array_agg(<str>{default})),
+            )
+        "###,
+            effective=setting.path("cfg::Config"),
+            database=setting.path("cfg::DatabaseConfig"),
+            instance=setting.path("cfg::InstanceConfig"),
+            default=default),
+        &(),
+    ).await?;
+    Ok(Values {
+        effective: setting.parse_json(&effective)?,
+        database: setting.parse_json(&database)?,
+        instance: setting.parse_json(&instance)?,
+        default: setting.parse_json(&default)?,
+    })
+}
+
+/// Casts values on the server to get the same representation as in
+/// `Values`. This also validates them.
+pub async fn normalize(cli: &mut Connection, setting: &Setting,
+                       values: &[String])
+    -> anyhow::Result<serde_json::Value>
+{
+    let data = cli.query_required_single::<String, _>(
+        &format!("SELECT to_str(<json>array_agg(<str>{}))",
+                 setting.value_expr(values)?),
+        &(),
+    ).await.with_context(|| format!("invalid value for {:?}", setting.name))?;
+    setting.parse_json(&data)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Setting, setting_name};
+
+    fn setting(type_name: &str, multi: bool) -> Setting {
+        Setting {
+            name: "x".into(),
+            type_name: type_name.into(),
+            property: "probes".into(),
+            target: "std::int64".into(),
+            multi,
+            system: false,
+            default: None,
+        }
+    }
+
+    #[test]
+    fn value_expr() {
+        let single = setting("cfg::AbstractConfig", false);
+        assert_eq!(single.value_expr(&["10".into()]).unwrap(),
+                   "<std::int64>'10'");
+        assert!(single.value_expr(&[]).is_err());
+        let multi = setting("cfg::AbstractConfig", true);
+        assert_eq!(multi.value_expr(&["1".into(), "2".into()]).unwrap(),
+                   "{<std::int64>'1', <std::int64>'2'}");
+        assert_eq!(multi.value_expr(&[]).unwrap(), "{}");
+    }
+
+    #[test]
+    fn path() {
+        assert_eq!(setting("cfg::AbstractConfig", false).path("cfg::Config"),
+                   "cfg::Config.probes");
+        assert_eq!(setting("ext::pgvector::Config", false)
+                   .path("cfg::Config"),
+                   "cfg::Config.extensions[IS ext::pgvector::Config].probes");
+    }
+
+    #[test]
+    fn name() {
+        assert_eq!(setting_name("cfg::AbstractConfig", "listen_port"),
+                   "listen_port");
+        assert_eq!(setting_name("ext::pgvector::Config", "probes"),
+                   "ext::pgvector::Config::probes");
+    }
+}
@@ -1,45 +1,69 @@
-use edgeql_parser::helpers::{quote_string, quote_name};
+use prettytable::{Table, Row, Cell};
+
+use edgeql_parser::helpers::quote_string;
+use is_terminal::IsTerminal;
+
 use crate::commands::Options;
+use crate::commands::config_auth;
+use crate::commands::config_file;
+use crate::commands::config_schema;
+use crate::commands::list;
+use crate::hint::HintExt;
 use crate::print;
 use crate::connect::Connection;
-use crate::commands::parser::{Configure, ConfigStr, ConfigBool};
-use crate::commands::parser::{AuthParameter};
+use crate::commands::parser::{Configure, ConfigStr, ConfigBool, ConfigScope};
+use crate::commands::parser::{AuthParameter, ConfigureShow};
+use crate::table;
+
+
+#[derive(serde::Serialize)]
+struct ShowItem<'a> {
+    name: &'a str,
+    #[serde(rename="type")]
+    target: &'a str,
+    value: serde_json::Value,
+    default: serde_json::Value,
+    source: &'static str,
+}
 
 
-async fn set_string(cli: &mut Connection, name: &str, value: &ConfigStr)
+async fn set_string(cli: &mut Connection, scope: &str, name: &str,
+    value: &ConfigStr)
     -> Result<(), anyhow::Error>
 {
     print::completion(&cli.execute(
-        &format!("CONFIGURE INSTANCE SET {} := {}",
-            name, quote_string(&value.value)),
+        &format!("CONFIGURE {} SET {} := {}",
+            scope, name, quote_string(&value.value)),
         &(),
     ).await?);
     Ok(())
 }
 
-async fn set_bool(cli: &mut Connection, name: &str, value: &ConfigBool)
+async fn set_bool(cli: &mut Connection, scope: &str, name: &str,
+    value: &ConfigBool)
     -> Result<(), anyhow::Error>
 {
     print::completion(&cli.execute(
-        &format!("CONFIGURE INSTANCE SET {} := {}",
-            name, if value.value { "true" } else { "false" }),
+        &format!("CONFIGURE {} SET {} := {}",
+            scope, name, if value.value { "true" } else { "false" }),
         &(),
     ).await?);
     Ok(())
 }
 
-async fn set_duration(cli: &mut Connection, name: &str, value: &ConfigStr)
+async fn set_duration(cli: &mut Connection, scope: &str, name: &str,
+    value: &ConfigStr)
     -> Result<(), anyhow::Error>
 {
     print::completion(&cli.execute(
-        &format!("CONFIGURE INSTANCE SET {} := <duration>{}",
-            name, quote_string(&value.value)),
+        &format!("CONFIGURE {} SET {} := <duration>{}",
+            scope, name, quote_string(&value.value)),
         &(),
     ).await?);
     Ok(())
 }
 
-pub async fn configure(cli: &mut Connection, _options: &Options,
+pub async fn configure(cli: &mut Connection, options: &Options,
     cfg: &Configure)
     -> Result<(), anyhow::Error>
 {
@@ -47,36 +71,53 @@ pub async fn configure(cli: &mut Connection, _options: &Options,
     use crate::commands::parser::ConfigureInsert as Ins;
     use crate::commands::parser::ConfigureReset as Res;
     use crate::commands::parser::ListParameter as I;
+    use crate::commands::parser::ConfigParameter as P;
     use crate::commands::parser::ConfigureSet as Set;
     use crate::commands::parser::ValueParameter as S;
+
+    let auth = matches!(&cfg.command,
+        C::Insert(Ins { parameter: I::Auth(_) })
+        | C::Reset(Res { parameter: P::Auth })
+        | C::Auth(_));
+    if auth && cfg.scope != ConfigScope::Instance {
+        return Err(anyhow::anyhow!(
+            "authentication rules can only be configured for the instance"
+        )).hint("Remove `--scope` or use `--scope=instance`")?;
+    }
+    if cfg.scope == ConfigScope::Session && options.command_line {
+        return Err(anyhow::anyhow!(
+            "session configuration is discarded when the command exits"
+        )).hint("Use `--scope=session` in the REPL \
+                 or `--scope=database` instead")?;
+    }
+    let scope = cfg.scope.as_keyword();
     match &cfg.command {
         C::Insert(Ins { parameter: I::Auth(param) }) => {
-            let AuthParameter { users, comment, priority, method } = param;
-            let mut props = vec![
-                format!("priority := {}", priority),
-                format!("method := (INSERT {})", quote_name(method)),
-            ];
-            let users = users.iter().map(|x| quote_string(x))
-                .collect::<Vec<_>>().join(", ");
-            if !users.is_empty() {
-                props.push(format!("user := {{ {} }}", users))
-            }
-            if let Some(ref comment_text) = comment {
-                props.push(format!(
-                    "comment := {}", quote_string(comment_text)))
-            }
-            print::completion(&cli.execute(&format!(r###"
-                CONFIGURE INSTANCE INSERT Auth {{
-                    {}
-                }}
-                "###,
-                props.join(",\n")
-            ), &()).await?);
+            let AuthParameter {
+                users, comment, priority, method, transports,
+            } = param;
+            let methods = config_auth::fetch_methods(cli).await?;
+            methods.check_transports(transports)?;
+            let rule = config_file::Auth {
+                priority: *priority,
+                user: users.clone(),
+                method: methods.canonical(method)?.into(),
+                transports: transports.clone(),
+                comment: comment.clone(),
+            };
+            print::completion(&cli.execute(
+                &config_file::insert_auth("INSTANCE", &rule),
+                &(),
+            ).await?);
             Ok(())
         }
+        C::Auth(auth) => {
+            config_auth::command(cli, options, &auth.subcommand).await
+        }
         C::Set(Set { parameter: S::ListenAddresses(param) }) => {
             print::completion(&cli.execute(
-                &format!("CONFIGURE INSTANCE SET listen_addresses := {{{}}}",
+                &format!("CONFIGURE {} SET listen_addresses := {{{}}}",
+                scope,
                 param.address.iter().map(|x| quote_string(x))
                     .collect::<Vec<_>>().join(", ")),
                 &(),
@@ -85,47 +126,72 @@ pub async fn configure(cli: &mut Connection, _options: &Options,
         }
         C::Set(Set { parameter: S::ListenPort(param) }) => {
             print::completion(&cli.execute(
-                &format!("CONFIGURE INSTANCE SET listen_port := {}",
-                         param.port),
+                &format!("CONFIGURE {} SET listen_port := {}",
+                         scope, param.port),
                 &(),
             ).await?);
             Ok(())
         }
         C::Set(Set { parameter: S::SharedBuffers(param) }) => {
-            set_string(cli, "shared_buffers", param).await
+            set_string(cli, scope, "shared_buffers", param).await
         }
         C::Set(Set { parameter: S::QueryWorkMem(param) }) => {
-            set_string(cli, "query_work_mem", param).await
+            set_string(cli, scope, "query_work_mem", param).await
         }
         C::Set(Set { parameter: S::MaintenanceWorkMem(param) }) => {
-            set_string(cli, "maintenance_work_mem", param).await
+            set_string(cli, scope, "maintenance_work_mem", param).await
         }
         C::Set(Set { parameter: S::EffectiveCacheSize(param) }) => {
-            set_string(cli, "effective_cache_size", param).await
+            set_string(cli, scope, "effective_cache_size", param).await
         }
         C::Set(Set { parameter: S::DefaultStatisticsTarget(param) }) => {
-            set_string(cli, "default_statistics_target", param).await
+            set_string(cli, scope, "default_statistics_target", param).await
         }
         C::Set(Set { parameter: S::EffectiveIoConcurrency(param) }) => {
-            set_string(cli, "effective_io_concurrency", param).await
+            set_string(cli, scope, "effective_io_concurrency", param).await
         }
         C::Set(Set { parameter: S::SessionIdleTimeout(param) }) => {
-            set_duration(cli, "session_idle_timeout", param).await
+            set_duration(cli, scope, "session_idle_timeout", param).await
         }
         C::Set(Set { parameter: S::SessionIdleTransactionTimeout(param) }) => {
-            set_duration(cli, "session_idle_transaction_timeout", param).await
+            set_duration(cli, scope,
+                         "session_idle_transaction_timeout", param).await
         }
         C::Set(Set { parameter: S::QueryExecutionTimeout(param) }) => {
-            set_duration(cli, "query_execution_timeout", param).await
+            set_duration(cli, scope, "query_execution_timeout", param).await
         }
         C::Set(Set { parameter: S::AllowBareDdl(param) }) => {
-            set_string(cli, "allow_bare_ddl", param).await
+            set_string(cli, scope, "allow_bare_ddl", param).await
         }
         C::Set(Set { parameter: S::ApplyAccessPolicies(param) }) => {
-            set_bool(cli, "apply_access_policies", param).await
+            set_bool(cli, scope, "apply_access_policies", param).await
         }
         C::Set(Set { parameter: S::AllowUserSpecifiedId(param) }) => {
-            set_bool(cli, "allow_user_specified_id", param).await
+            set_bool(cli, scope, "allow_user_specified_id", param).await
+        }
+        C::Set(Set { parameter: S::Other(args) }) => {
+            let Some((name, values)) = args.split_first() else {
+                anyhow::bail!("setting name is required");
+            };
+            let settings = config_schema::fetch_settings(cli).await?;
+            let setting = config_schema::find(&settings, name)?;
+            if setting.system && cfg.scope != ConfigScope::Instance {
+                return Err(anyhow::anyhow!(
+                    "setting {:?} can only be configured for the instance",
+                    setting.name,
+                )).hint("Use `--scope=instance`")?;
+            }
+            print::completion(&cli.execute(
+                &format!("CONFIGURE {} SET {} := {}",
+                         scope, setting.name, setting.value_expr(values)?),
+                &(),
+            ).await?);
+            Ok(())
+        }
+        C::Show(show) => self::show(cli, options, show).await,
+        C::Export => config_file::export(cli).await,
+        C::Apply(apply) => {
+            config_file::apply(cli, &apply.file, apply.dry_run).await
         }
         C::Reset(Res { parameter }) => {
             use crate::commands::parser::ConfigParameter as C;
@@ -146,12 +212,82 @@ pub async fn configure(cli: &mut Connection, _options: &Options,
                 C::AllowBareDdl => "allow_bare_ddl",
                 C::ApplyAccessPolicies => "apply_access_policies",
                 C::AllowUserSpecifiedId => "allow_user_specified_id",
+                C::Other(args) => {
+                    let [name] = &args[..] else {
+                        anyhow::bail!("`configure reset` expects \
+                                       exactly one setting name");
+                    };
+                    let settings = config_schema::fetch_settings(cli).await?;
+                    config_schema::find(&settings, name)?;
+                    &name[..]
+                }
             };
             print::completion(&cli.execute(
-                &format!("CONFIGURE INSTANCE RESET {}", name),
+                &format!("CONFIGURE {} RESET {}", scope, name),
                 &(),
             ).await?);
             Ok(())
         }
     }
 }
+
+async fn show(cli: &mut Connection, options: &Options, show: &ConfigureShow)
+    -> Result<(), anyhow::Error>
+{
+    let settings = config_schema::fetch_settings(cli).await?;
+    let settings = match &show.name {
+        Some(name) => vec![config_schema::find(&settings, name)?.clone()],
+        None => settings,
+    };
+    let mut items = Vec::with_capacity(settings.len());
+    for setting in &settings {
+        items.push(config_schema::fetch_values(cli, setting).await?);
+    }
+    if show.json {
+        let items = settings.iter().zip(items)
+            .map(|(setting, values)| ShowItem {
+                name: &setting.name,
+                target: &setting.target,
+                source: values.source(),
+                value: values.effective.clone(),
+                default: values.default,
+            })
+            .collect::<Vec<_>>();
+        return list::print_json(&items);
+    }
+    if !options.command_line || std::io::stdout().is_terminal() {
+        let mut table = Table::new();
+        table.set_format(*table::FORMAT);
+        table.set_titles(Row::new(
+            ["Name", "Value", "Source"]
+            .iter().map(|x| table::header_cell(x)).collect()));
+        for (setting, values) in settings.iter().zip(&items) {
+            table.add_row(Row::new(vec![
+                Cell::new(&setting.name),
+                Cell::new(&display_value(&values.effective)),
+                Cell::new(values.source()),
+            ]));
+        }
+        table.printstd();
+    } else {
+        for (setting, values) in settings.iter().zip(&items) {
+            println!("{}\t{}\t{}", setting.name,
+                     display_value(&values.effective), values.source());
+        }
+    }
+    Ok(())
+}
+
+fn display_value(value: &serde_json::Value) -> String {
+    use serde_json::Value;
+
+    match value {
+        Value::Null => String::from("{}"),
+        Value::String(s) => s.clone(),
+        Value::Array(items) => {
+            let items = items.iter().map(display_value).collect::<Vec<_>>();
+            format!("{{{}}}", items.join(", "))
+        }
+        value => value.to_string(),
+    }
+}
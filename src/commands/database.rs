@@ -1,8 +1,11 @@
 use edgeql_parser::helpers::quote_name;
+use indicatif::{ProgressBar, HumanBytes};
+use tokio_stream::StreamExt;
 
 use crate::commands::parser::{CreateDatabase, DropDatabase, WipeDatabase};
+use crate::commands::parser::{RenameDatabase, CloneDatabase};
 use crate::commands::{ExitCode, Options};
-use crate::connect::Connection;
+use crate::connect::{Connection, Connector};
 use crate::hint::HintExt;
 use crate::portable::exit_codes;
 use crate::print;
@@ -65,3 +68,88 @@ pub async fn wipe(cli: &mut Connection, options: &WipeDatabase, _: &Options)
     print::completion(&status);
     Ok(())
 }
+
+pub async fn rename(cli: &mut Connection, options: &RenameDatabase, _: &Options)
+    -> Result<(), anyhow::Error>
+{
+    if cli.get_version().await?.specific() < "5.0-alpha.1".parse().unwrap() {
+        return Err(
+            anyhow::anyhow!("The `database rename` command is only \
+                            supported in EdgeDB >= 5.0")
+        ).hint("Use `edgedb database clone`, `edgedb database drop`")?;
+    }
+    if cli.database() == options.old_name {
+        return Err(
+            anyhow::anyhow!("cannot rename database {:?} \
+                            while connected to it", options.old_name)
+        ).hint("Connect to another database, e.g. `--database=edgedb`")?;
+    }
+    let status = cli.execute(
+        &format!("ALTER BRANCH {} RENAME TO {}",
+                 quote_name(&options.old_name),
+                 quote_name(&options.new_name)),
+        &(),
+    ).await?;
+    print::completion(&status);
+    Ok(())
+}
+
+pub async fn clone(cli: &mut Connection, options: &CloneDatabase,
+                   general: &Options)
+    -> Result<(), anyhow::Error>
+{
+    let mut conn_params = general.conn_params.clone();
+    let mut source = if cli.database() == options.source {
+        None
+    } else {
+        Some(conn_params.database(&options.source)?.connect().await?)
+    };
+    cli.execute(
+        &format!("CREATE DATABASE {}", quote_name(&options.target)),
+        &(),
+    ).await?;
+    let result = match &mut source {
+        Some(source) => {
+            copy_data(source, &mut conn_params, &options.target).await
+        }
+        None => copy_data(cli, &mut conn_params, &options.target).await,
+    };
+    if let Err(e) = result {
+        // connection to the target database is closed at this point
+        let drop = format!("DROP DATABASE {}", quote_name(&options.target));
+        if let Err(drop_err) = cli.execute(&drop, &()).await {
+            log::warn!("Cannot remove incomplete database {:?}: {:#}",
+                       options.target, drop_err);
+        }
+        return Err(e);
+    }
+    print::success(format!("Database {:?} cloned into {:?}.",
+                           options.source, options.target));
+    Ok(())
+}
+
+/// Streams dump of the source database right into restore of the target
+/// one, so no intermediate file is needed
+async fn copy_data(source: &mut Connection, conn_params: &mut Connector,
+                   target: &str)
+    -> Result<(), anyhow::Error>
+{
+    let include_secrets = source.get_version().await?.specific()
+        >= "4.0-alpha.2".parse().unwrap();
+    let source_name = source.database().to_string();
+    let mut target_conn = conn_params.database(target)?.connect().await?;
+
+    let (header, blocks) = source.dump(include_secrets).await?;
+    let bar = ProgressBar::new_spinner();
+    let mut processed = 0;
+    let blocks = blocks.map(|packet| packet.map(|packet| {
+        processed += packet.data.len();
+        bar.set_message(format!("Cloning {source_name}: {} copied.",
+                                HumanBytes(processed as u64)));
+        bar.tick();
+        packet.data
+    }));
+    target_conn.restore(header.data, blocks).await?;
+    bar.finish_and_clear();
+    Ok(())
+}
@@ -1,11 +1,20 @@
 use crate::commands::Options;
 use crate::commands::helpers::quote_namespaced;
+use crate::commands::list;
 use crate::connect::Connection;
+use crate::error_display::suggest_names;
 use crate::highlight;
+use crate::hint::HintExt;
 
 
+#[derive(serde::Serialize)]
+struct DescribeJson<'a> {
+    name: &'a str,
+    descriptions: &'a [String],
+}
+
 pub async fn describe(cli: &mut Connection, options: &Options,
-    name: &str, verbose: bool)
+    name: &str, verbose: bool, json: bool)
     -> Result<(), anyhow::Error>
 {
     let items = cli.query::<String, _>(
@@ -13,7 +22,24 @@ pub async fn describe(cli: &mut Connection, options: &Options,
             name=quote_namespaced(name),
             flag=if verbose { "VERBOSE" } else {""}),
         &(),
-    ).await?;
+    ).await;
+    let items = match items {
+        Ok(items) => items,
+        Err(e) => {
+            if let Some(hint) = suggest_names(cli, &e).await {
+                return Err(anyhow::Error::from(e).with_hint(|| hint))?;
+            }
+            return Err(e)?;
+        }
+    };
+    if json {
+        // there may be several objects of the same name (e.g. overloaded
+        // functions), so descriptions are always an array
+        return list::print_json(&DescribeJson {
+            name,
+            descriptions: &items,
+        });
+    }
     for text in items {
         if let Some(ref styler) = options.styler {
             let mut out = String::with_capacity(text.len());
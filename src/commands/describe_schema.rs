@@ -1,15 +1,25 @@
 use crate::commands::Options;
+use crate::commands::list;
 use crate::connect::Connection;
 use crate::highlight;
 
 
-pub async fn describe_schema(cli: &mut Connection, options: &Options)
+#[derive(serde::Serialize)]
+struct SchemaJson<'a> {
+    sdl: &'a str,
+}
+
+pub async fn describe_schema(cli: &mut Connection, options: &Options,
+    json: bool)
     -> Result<(), anyhow::Error>
 {
     let text = cli.query_required_single::<String, ()>(
         "DESCRIBE SCHEMA AS SDL",
         &(),
     ).await?;
+    if json {
+        return list::print_json(&SchemaJson { sdl: &text });
+    }
     if let Some(ref styler) = options.styler {
         let mut out = String::with_capacity(text.len());
         highlight::edgeql(&mut out, &text, styler);
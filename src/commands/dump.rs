@@ -1,22 +1,48 @@
 use std::path::{Path, PathBuf};
 
 use anyhow::Context;
-use indicatif::{ProgressBar, HumanBytes};
+use async_compression::Level;
+use async_compression::tokio::write::{GzipEncoder, ZstdEncoder};
+use futures_util::{stream, TryStreamExt};
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use indicatif::{MultiProgress, ProgressBar, HumanBytes};
 use tokio::fs;
 use tokio::io::{self, AsyncWrite, AsyncWriteExt};
+use tokio_util::compat::{FuturesAsyncWriteCompatExt, TokioAsyncWriteCompatExt};
 use sha1::Digest;
 
 use tokio_stream::StreamExt;
 
 use crate::commands::Options;
+use crate::commands::dump_file::{dump_file_async, extract_sdl};
 use crate::commands::list_databases::get_databases;
-use crate::commands::parser::{Dump as DumpOptions, DumpFormat};
+use crate::commands::parser::{Dump as DumpOptions, DumpCmd, DumpFormat};
+use crate::commands::parser::{AllDatabases, Compression, CompressionAlgorithm};
 use crate::connect::Connection;
 use crate::platform::tmp_file_name;
+use crate::print;
 
 
 type Output = Box<dyn AsyncWrite + Unpin + Send>;
 
+/// Transformations applied to the dump before it's written to a file
+#[derive(Default)]
+pub struct Filters {
+    pub compression: Option<Compression>,
+    pub encryption: Option<Encryption>,
+}
+
+pub enum Encryption {
+    Recipients(Vec<age::x25519::Recipient>),
+    Passphrase(String),
+}
+
+/// Databases chosen by `--include-db` and `--exclude-db` patterns
+pub struct Selection {
+    include: Option<GlobSet>,
+    exclude: GlobSet,
+}
+
 
 pub struct Guard {
     filenames: Option<(PathBuf, PathBuf)>,
@@ -47,7 +73,9 @@ impl Guard {
             }))
         }
     }
-    async fn commit(self) -> anyhow::Result<()> {
+    async fn commit(self, mut output: Output) -> anyhow::Result<()> {
+        // finishes compressed or encrypted stream and flushes the file
+        output.shutdown().await?;
         if let Some((tmp_filename, filename)) = self.filenames {
             fs::rename(tmp_filename, filename).await?;
         }
@@ -60,6 +88,21 @@ pub async fn dump(cli: &mut Connection, general: &Options,
     options: &DumpOptions)
     -> Result<(), anyhow::Error>
 {
+    if let Some(cmd) = &options.subcommand {
+        return match cmd {
+            DumpCmd::ExtractSchema(c) if c.sdl => {
+                extract_sdl(cli, general, c).await
+            }
+            _ => dump_file_async(cmd).await,
+        };
+    }
+    let Some(path) = &options.path else {
+        anyhow::bail!("path to dump file is required");
+    };
+    let filters = Filters {
+        compression: options.compress,
+        encryption: Encryption::from_options(options).await?,
+    };
     if options.all {
         if let Some(dformat) = options.format {
             if dformat != DumpFormat::Dir {
@@ -68,17 +111,122 @@ pub async fn dump(cli: &mut Connection, general: &Options,
         } else {
             anyhow::bail!("`--format=dir` is required when using `--all`");
         }
-        dump_all(cli, general, options.path.as_ref(), options.include_secrets).await
+        dump_all(cli, general, path, options.include_secrets, &filters,
+                 &options.databases).await
     } else {
         if options.format.is_some() {
             anyhow::bail!("`--format` is reserved for dump using `--all`");
         }
-        dump_db(cli, general, options.path.as_ref(), options.include_secrets).await
+        dump_db(cli, general, path, options.include_secrets, &filters,
+                &MultiProgress::new()).await
+    }
+}
+
+impl Selection {
+    pub fn new(options: &AllDatabases) -> anyhow::Result<Selection> {
+        fn build(patterns: &[String]) -> anyhow::Result<GlobSet> {
+            let mut set = GlobSetBuilder::new();
+            for pattern in patterns {
+                set.add(Glob::new(pattern).with_context(|| {
+                    format!("invalid database pattern {:?}", pattern)
+                })?);
+            }
+            Ok(set.build()?)
+        }
+        let include = if options.include_db.is_empty() {
+            None
+        } else {
+            Some(build(&options.include_db)?)
+        };
+        Ok(Selection {
+            include,
+            exclude: build(&options.exclude_db)?,
+        })
+    }
+    pub fn matches(&self, database: &str) -> bool {
+        self.include.as_ref().map(|set| set.is_match(database)).unwrap_or(true)
+            && !self.exclude.is_match(database)
+    }
+}
+
+impl Encryption {
+    async fn from_options(options: &DumpOptions)
+        -> anyhow::Result<Option<Encryption>>
+    {
+        if let Some(path) = &options.passphrase_file {
+            let passphrase = read_passphrase(path).await?;
+            return Ok(Some(Encryption::Passphrase(passphrase)));
+        }
+        if options.encrypt_to.is_empty() {
+            return Ok(None);
+        }
+        let recipients = options.encrypt_to.iter()
+            .map(|key| key.parse().map_err(|e| {
+                anyhow::anyhow!("invalid recipient {:?}: {}", key, e)
+            }))
+            .collect::<anyhow::Result<_>>()?;
+        Ok(Some(Encryption::Recipients(recipients)))
+    }
+    fn wrap(&self, output: Output) -> anyhow::Result<Output> {
+        let encryptor = match self {
+            Encryption::Recipients(recipients) => {
+                let recipients = recipients.iter()
+                    .map(|r| -> Box<dyn age::Recipient + Send> {
+                        Box::new(r.clone())
+                    })
+                    .collect();
+                age::Encryptor::with_recipients(recipients)
+                    .expect("at least one recipient")
+            }
+            Encryption::Passphrase(passphrase) => {
+                age::Encryptor::with_user_passphrase(
+                    age::secrecy::Secret::new(passphrase.clone()))
+            }
+        };
+        let writer = encryptor.wrap_async_output(output.compat_write())?;
+        Ok(Box::new(writer.compat_write()))
+    }
+}
+
+/// Reads passphrase from the file, trailing newline is ignored
+pub async fn read_passphrase(path: &Path) -> anyhow::Result<String> {
+    let data = fs::read_to_string(path).await
+        .with_context(|| format!("cannot read {}", path.display()))?;
+    let passphrase = data.trim_end_matches(&['\r', '\n'][..]);
+    if passphrase.is_empty() {
+        anyhow::bail!("passphrase in {} is empty", path.display());
+    }
+    Ok(passphrase.into())
+}
+
+/// Compresses the data first, as encrypted data can't be compressed
+fn wrap_output(output: Output, filters: &Filters) -> anyhow::Result<Output> {
+    let output = match &filters.encryption {
+        Some(encryption) => encryption.wrap(output)?,
+        None => output,
+    };
+    Ok(compress(output, filters.compression))
+}
+
+fn compress(output: Output, compression: Option<Compression>) -> Output {
+    let Some(compression) = compression else {
+        return output;
+    };
+    let level = compression.level.map(Level::Precise)
+        .unwrap_or(Level::Default);
+    match compression.algorithm {
+        CompressionAlgorithm::Zstd => {
+            Box::new(ZstdEncoder::with_quality(output, level))
+        }
+        CompressionAlgorithm::Gzip => {
+            Box::new(GzipEncoder::with_quality(output, level))
+        }
     }
 }
 
 async fn dump_db(cli: &mut Connection, _options: &Options, filename: &Path,
-                 mut include_secrets: bool)
+                 mut include_secrets: bool, filters: &Filters,
+                 bars: &MultiProgress)
     -> Result<(), anyhow::Error>
 {
     if cli.get_version().await?.specific() < "4.0-alpha.2".parse().unwrap() {
@@ -86,9 +234,10 @@ async fn dump_db(cli: &mut Connection, _options: &Options, filename: &Path,
     }
 
     let dbname = cli.database().to_string();
-    eprintln!("Starting dump for {dbname}...");
+    bars.suspend(|| eprintln!("Starting dump for {dbname}..."));
 
-    let (mut output, guard) = Guard::open(filename).await?;
+    let (output, guard) = Guard::open(filename).await?;
+    let mut output = wrap_output(output, filters)?;
     output.write_all(
         b"\xFF\xD8\x00\x00\xD8EDGEDB\x00DUMP\x00\
           \x00\x00\x00\x00\x00\x00\x00\x01"
@@ -107,7 +256,7 @@ async fn dump_db(cli: &mut Connection, _options: &Options, filename: &Path,
     output.write_all(&header_buf).await?;
     output.write_all(&header.data).await?;
 
-    let bar = ProgressBar::new_spinner();
+    let bar = bars.add(ProgressBar::new_spinner());
     let mut processed = 0;
 
     while let Some(packet) = blocks.next().await.transpose()? {
@@ -127,22 +276,34 @@ async fn dump_db(cli: &mut Connection, _options: &Options, filename: &Path,
         output.write_all(&header_buf).await?;
         output.write_all(&packet.data).await?;
     }
-    guard.commit().await?;
+    guard.commit(output).await?;
     bar.abandon_with_message(format!("Finished dump for {dbname}. Total size: {}", HumanBytes(processed as u64)));
     Ok(())
 }
 
 pub async fn dump_all(cli: &mut Connection, options: &Options, dir: &Path,
-                      include_secrets: bool)
+                      include_secrets: bool, filters: &Filters,
+                      all: &AllDatabases)
     -> Result<(), anyhow::Error>
 {
-    let databases = get_databases(cli).await?;
+    let selection = Selection::new(all)?;
+    let databases = get_databases(cli).await?.into_iter()
+        .filter(|name| selection.matches(name))
+        .collect::<Vec<_>>();
+    if databases.is_empty() {
+        print::warn("No databases match the specified patterns.");
+    }
     let config: String = cli.query_required_single("DESCRIBE SYSTEM CONFIG", &()).await?;
     let roles: String = cli.query_required_single("DESCRIBE ROLES", &()).await?;
 
     fs::create_dir_all(dir).await?;
 
-    let (mut init, guard) = Guard::open(&dir.join("init.edgeql")).await?;
+    let (init, guard) = Guard::open(&dir.join("init.edgeql")).await?;
+    // roles and config may contain secrets, so encrypt them too
+    let mut init = match &filters.encryption {
+        Some(encryption) => encryption.wrap(init)?,
+        None => init,
+    };
     if !config.trim().is_empty() {
         init.write_all(b"# DESCRIBE SYSTEM CONFIG\n").await?;
         init.write_all(config.as_bytes()).await?;
@@ -153,16 +314,24 @@ pub async fn dump_all(cli: &mut Connection, options: &Options, dir: &Path,
         init.write_all(roles.as_bytes()).await?;
         init.write_all(b"\n").await?;
     }
-    guard.commit().await?;
-
-    let mut conn_params = options.conn_params.clone();
-    for database in &databases {
-        let mut db_conn = conn_params
-            .database(database)?
-            .connect().await?;
-        let filename = dir.join(&(urlencoding::encode(database) + ".dump")[..]);
-        dump_db(&mut db_conn, options, &filename, include_secrets).await?;
-    }
+    guard.commit(init).await?;
+
+    let bars = MultiProgress::new();
+    let jobs = usize::from(all.jobs.unwrap_or(1));
+    stream::iter(databases.iter().map(Ok::<_, anyhow::Error>))
+        .try_for_each_concurrent(jobs, |database| {
+            let mut conn_params = options.conn_params.clone();
+            let bars = &bars;
+            async move {
+                let mut db_conn = conn_params
+                    .database(database)?
+                    .connect().await?;
+                let filename = dir.join(
+                    &(urlencoding::encode(database) + ".dump")[..]);
+                dump_db(&mut db_conn, options, &filename, include_secrets,
+                        filters, bars).await
+            }
+        }).await?;
 
     Ok(())
 }
@@ -0,0 +1,318 @@
+use std::collections::BTreeMap;
+use std::convert::TryInto;
+use std::ffi::OsString;
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+
+use anyhow::Context;
+use bytes::{Buf, BytesMut};
+use edgeql_parser::helpers::quote_name;
+use indicatif::HumanBytes;
+use tokio::fs;
+
+use crate::commands::Options;
+use crate::commands::parser::{DumpCmd, DumpInspect, DumpVerify};
+use crate::commands::parser::{DecryptOptions, DumpExtractSchema};
+use crate::commands::list;
+use crate::commands::restore::{Input, PacketType, read_packet, read_version};
+use crate::commands::restore::{decompress, decrypt};
+use crate::connect::Connection;
+use crate::print;
+
+
+const HEADER_SERVER_TIME: u16 = 102;
+const HEADER_SERVER_VER: u16 = 103;
+const HEADER_SERVER_CATALOG_VERSION: u16 = 105;
+
+/// Everything that can be learned about the dump without a server
+#[derive(serde::Serialize, Debug, Default)]
+struct Summary {
+    format_version: i64,
+    encryption: Option<&'static str>,
+    compression: Option<&'static str>,
+    protocol_version: Option<String>,
+    server_version: Option<String>,
+    #[serde(with="humantime_serde")]
+    created: Option<SystemTime>,
+    catalog_version: Option<u64>,
+    schema_ddl_size: Option<u64>,
+    schema_types: Option<u64>,
+    header_size: u64,
+    blocks: u64,
+    data_size: u64,
+    total_size: u64,
+}
+
+/// Metadata stored in the dump header by the server
+#[derive(Debug, Default)]
+struct HeaderInfo {
+    attributes: BTreeMap<u16, Vec<u8>>,
+    protocol_version: (u16, u16),
+    schema_ddl: Vec<u8>,
+    /// Number of type descriptors, i.e. all schema types (scalars,
+    /// collections, ...), not only object types
+    schema_types: u64,
+}
+
+#[tokio::main]
+pub async fn dump_file(cmd: &DumpCmd) -> anyhow::Result<()> {
+    dump_file_async(cmd).await
+}
+
+pub async fn dump_file_async(cmd: &DumpCmd) -> anyhow::Result<()> {
+    match cmd {
+        DumpCmd::Verify(c) => verify(c).await,
+        DumpCmd::Inspect(c) => inspect(c).await,
+        DumpCmd::ExtractSchema(c) if c.sdl => {
+            anyhow::bail!("`--sdl` requires a connection to the server");
+        }
+        DumpCmd::ExtractSchema(c) => {
+            let ddl = read_schema(&c.path, &c.decrypt).await?;
+            write_schema(c, &ddl, "schema.edgeql").await
+        }
+    }
+}
+
+async fn verify(cmd: &DumpVerify) -> anyhow::Result<()> {
+    if fs::metadata(&cmd.path).await?.is_dir() {
+        let dump_ext = OsString::from("dump");
+        let mut files = Vec::new();
+        let mut dir_list = fs::read_dir(&cmd.path).await?;
+        while let Some(entry) = dir_list.next_entry().await? {
+            let path = entry.path();
+            if path.extension() == Some(&dump_ext) {
+                files.push(path);
+            }
+        }
+        files.sort();
+        let mut failed = 0;
+        for path in &files {
+            match read(path, &cmd.decrypt).await {
+                Ok(_) => print::success_msg("Verified", path.display()),
+                Err(e) => {
+                    print::error(format!("{}: {:#}", path.display(), e));
+                    failed += 1;
+                }
+            }
+        }
+        if failed > 0 {
+            anyhow::bail!("{} of {} dumps are corrupted", failed, files.len());
+        }
+        Ok(())
+    } else {
+        let summary = read(&cmd.path, &cmd.decrypt).await?;
+        print::success_msg("Dump is valid",
+            format!("{} blocks, {}", summary.blocks,
+                    HumanBytes(summary.total_size)));
+        Ok(())
+    }
+}
+
+async fn inspect(cmd: &DumpInspect) -> anyhow::Result<()> {
+    let summary = read(&cmd.path, &cmd.decrypt).await?;
+    if cmd.json {
+        return list::print_json(&summary);
+    }
+    let unknown = || String::from("unknown");
+    println!("Format version: {}", summary.format_version);
+    println!("Encryption: {}", summary.encryption.unwrap_or("none"));
+    println!("Compression: {}", summary.compression.unwrap_or("none"));
+    println!("Protocol version: {}",
+             summary.protocol_version.clone().unwrap_or_else(unknown));
+    println!("Server version: {}",
+             summary.server_version.clone().unwrap_or_else(unknown));
+    println!("Created: {}", summary.created
+             .map(|t| humantime::format_rfc3339_seconds(t).to_string())
+             .unwrap_or_else(unknown));
+    println!("Catalog version: {}", summary.catalog_version
+             .map(|v| v.to_string()).unwrap_or_else(unknown));
+    println!("Schema DDL size: {}", summary.schema_ddl_size
+             .map(|v| HumanBytes(v).to_string()).unwrap_or_else(unknown));
+    println!("Schema types: {}", summary.schema_types
+             .map(|v| v.to_string()).unwrap_or_else(unknown));
+    println!("Header size: {}", HumanBytes(summary.header_size));
+    println!("Data blocks: {}", summary.blocks);
+    println!("Data size: {}", HumanBytes(summary.data_size));
+    println!("Total size: {}", HumanBytes(summary.total_size));
+    Ok(())
+}
+
+/// Applies the schema from the dump to a temporary database to describe
+/// it as SDL
+pub async fn extract_sdl(cli: &mut Connection, options: &Options,
+                         cmd: &DumpExtractSchema)
+    -> anyhow::Result<()>
+{
+    let ddl = read_schema(&cmd.path, &cmd.decrypt).await?;
+    let database = format!("extract_schema_{:08x}", rand::random::<u32>());
+    cli.execute(&format!("CREATE DATABASE {}", quote_name(&database)), &())
+        .await?;
+    let result = describe_sdl(options, &database, &ddl).await;
+    let drop = format!("DROP DATABASE {}", quote_name(&database));
+    if let Err(e) = cli.execute(&drop, &()).await {
+        print::error(format!("Cannot drop temporary database {:?}: {:#}",
+                             database, e));
+    }
+    write_schema(cmd, &result?, "default.esdl").await
+}
+
+async fn describe_sdl(options: &Options, database: &str, ddl: &str)
+    -> anyhow::Result<String>
+{
+    let mut conn_params = options.conn_params.clone();
+    let mut cli = conn_params.database(database)?.connect().await?;
+    cli.execute(ddl, &()).await
+        .context("cannot apply schema from the dump")?;
+    let sdl = cli.query_required_single("DESCRIBE SCHEMA AS SDL", &()).await?;
+    Ok(sdl)
+}
+
+async fn write_schema(cmd: &DumpExtractSchema, text: &str, filename: &str)
+    -> anyhow::Result<()>
+{
+    let Some(dir) = &cmd.out else {
+        println!("{}", text);
+        return Ok(());
+    };
+    fs::create_dir_all(dir).await
+        .with_context(|| format!("cannot create {}", dir.display()))?;
+    let path = dir.join(filename);
+    fs::write(&path, text).await
+        .with_context(|| format!("cannot write {}", path.display()))?;
+    print::success_msg("Schema written to", path.display());
+    Ok(())
+}
+
+/// Reads schema DDL from the dump header, data blocks are not read
+async fn read_schema(path: &Path, decrypt_options: &DecryptOptions)
+    -> anyhow::Result<String>
+{
+    let ctx = || format!("Failed to read dump {}", path.display());
+    let file = fs::File::open(path).await.with_context(ctx)?;
+    let (input, _) = decrypt(Box::new(file) as Input, decrypt_options).await
+        .with_context(ctx)?;
+    let (mut input, _) = decompress(input).await.with_context(ctx)?;
+    read_version(&mut input).await.with_context(ctx)?;
+    let mut buf = BytesMut::with_capacity(65536);
+    let header = read_packet(&mut input, &mut buf, PacketType::Header).await
+        .with_context(ctx)?
+        .ok_or_else(|| anyhow::anyhow!("Dump is empty"))
+        .with_context(ctx)?;
+    let info = parse_header(&header).with_context(ctx)?;
+    String::from_utf8(info.schema_ddl)
+        .context("schema in the dump is not valid UTF-8")
+}
+
+/// Reads the whole dump validating the signature and checksums
+async fn read(path: &Path, decrypt_options: &DecryptOptions)
+    -> anyhow::Result<Summary>
+{
+    let ctx = || format!("Failed to read dump {}", path.display());
+    let file = fs::File::open(path).await.with_context(ctx)?;
+    let total_size = file.metadata().await?.len();
+    let (input, encryption) = decrypt(Box::new(file) as Input,
+                                      decrypt_options).await
+        .with_context(ctx)?;
+    let (mut input, compression) = decompress(input).await
+        .with_context(ctx)?;
+    let mut summary = Summary {
+        format_version: read_version(&mut input).await.with_context(ctx)?,
+        encryption,
+        compression,
+        total_size,
+        ..Default::default()
+    };
+    let mut buf = BytesMut::with_capacity(65536);
+    let header = read_packet(&mut input, &mut buf, PacketType::Header).await
+        .with_context(ctx)?
+        .ok_or_else(|| anyhow::anyhow!("Dump is empty"))
+        .with_context(ctx)?;
+    summary.header_size = header.len() as u64;
+    match parse_header(&header) {
+        Ok(info) => summary.fill(&info),
+        Err(e) => log::warn!("Cannot parse dump header: {:#}", e),
+    }
+    while let Some(block) = read_packet(&mut input, &mut buf,
+                                        PacketType::Block)
+        .await.with_context(ctx)?
+    {
+        summary.blocks += 1;
+        summary.data_size += block.len() as u64;
+    }
+    Ok(summary)
+}
+
+impl Summary {
+    fn fill(&mut self, info: &HeaderInfo) {
+        let (major, minor) = info.protocol_version;
+        self.protocol_version = Some(format!("{}.{}", major, minor));
+        self.server_version = info.attributes.get(&HEADER_SERVER_VER)
+            .map(|v| String::from_utf8_lossy(v).into_owned());
+        self.created = info.attributes.get(&HEADER_SERVER_TIME)
+            .and_then(|v| std::str::from_utf8(v).ok())
+            .and_then(|v| v.parse::<u64>().ok())
+            .map(|secs| SystemTime::UNIX_EPOCH + Duration::from_secs(secs));
+        self.catalog_version = info.attributes
+            .get(&HEADER_SERVER_CATALOG_VERSION)
+            .and_then(|v| v[..].try_into().ok())
+            .map(u64::from_be_bytes);
+        self.schema_ddl_size = Some(info.schema_ddl.len() as u64);
+        self.schema_types = Some(info.schema_types);
+    }
+}
+
+/// Parses the beginning of the `DumpHeader` message, the type descriptors
+/// that follow are opaque to the client
+fn parse_header(mut data: &[u8]) -> anyhow::Result<HeaderInfo> {
+    fn ensure(data: &[u8], len: usize) -> anyhow::Result<()> {
+        if data.remaining() < len {
+            anyhow::bail!("header is truncated");
+        }
+        Ok(())
+    }
+    let mut info = HeaderInfo::default();
+    ensure(data, 2)?;
+    for _ in 0..data.get_u16() {
+        ensure(data, 6)?;
+        let code = data.get_u16();
+        let len = data.get_u32() as usize;
+        ensure(data, len)?;
+        info.attributes.insert(code, data[..len].to_vec());
+        data.advance(len);
+    }
+    ensure(data, 4)?;
+    info.protocol_version = (data.get_u16(), data.get_u16());
+    ensure(data, 4)?;
+    let ddl_len = data.get_u32() as usize;
+    ensure(data, ddl_len)?;
+    info.schema_ddl = data[..ddl_len].to_vec();
+    data.advance(ddl_len);
+    ensure(data, 4)?;
+    info.schema_types = data.get_u32() as u64;
+    Ok(info)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{parse_header, HEADER_SERVER_VER};
+
+    #[test]
+    fn header() {
+        let mut data = Vec::new();
+        data.extend(1u16.to_be_bytes());
+        data.extend(HEADER_SERVER_VER.to_be_bytes());
+        data.extend(3u32.to_be_bytes());
+        data.extend(b"3.0");
+        data.extend(1u16.to_be_bytes());
+        data.extend(0u16.to_be_bytes());
+        data.extend(4u32.to_be_bytes());
+        data.extend(b"DDL;");
+        data.extend(2u32.to_be_bytes());
+        let info = parse_header(&data).unwrap();
+        assert_eq!(info.attributes[&HEADER_SERVER_VER], b"3.0");
+        assert_eq!(info.protocol_version, (1, 0));
+        assert_eq!(info.schema_ddl, b"DDL;");
+        assert_eq!(info.schema_types, 2);
+        assert!(parse_header(&data[..10]).is_err());
+    }
+}
@@ -3,6 +3,7 @@ use edgedb_tokio::server_params::PostgresAddress;
 
 use crate::analyze;
 use crate::commands::parser::{Common, DatabaseCmd, ListCmd, DescribeCmd};
+use crate::commands::parser::RoleCmd;
 use crate::commands::{self, Options};
 use crate::migrations::options::{MigrationCmd};
 use crate::migrations;
@@ -17,34 +18,69 @@ pub async fn common(cli: &mut Connection, cmd: &Common, options: &Options)
         List(c) => match &c.subcommand {
             ListCmd::Aliases(c) => {
                 commands::list_aliases(cli, &options,
-                    &c.pattern, c.system, c.case_sensitive, c.verbose).await?;
+                    &c.pattern, c.system, c.case_sensitive, c.verbose,
+                    c.json).await?;
             }
             ListCmd::Casts(c) => {
                 commands::list_casts(cli, &options,
-                    &c.pattern, c.case_sensitive).await?;
+                    &c.pattern, c.case_sensitive, c.json).await?;
+            }
+            ListCmd::Constraints(c) => {
+                commands::list_constraints(cli, &options,
+                    &c.pattern, c.system, c.case_sensitive, c.json).await?;
+            }
+            ListCmd::Extensions(c) => {
+                commands::list_extensions(cli, &options,
+                    &c.pattern, c.case_sensitive, c.json).await?;
+            }
+            ListCmd::Globals(c) => {
+                commands::list_globals(cli, &options,
+                    &c.pattern, c.case_sensitive, c.json).await?;
+            }
+            ListCmd::Policies(c) => {
+                commands::list_policies(cli, &options,
+                    &c.pattern, c.case_sensitive, c.json).await?;
+            }
+            ListCmd::Rewrites(c) => {
+                commands::list_rewrites(cli, &options,
+                    &c.pattern, c.case_sensitive, c.json).await?;
+            }
+            ListCmd::Triggers(c) => {
+                commands::list_triggers(cli, &options,
+                    &c.pattern, c.case_sensitive, c.json).await?;
+            }
+            ListCmd::Functions(c) => {
+                commands::list_functions(cli, &options,
+                    &c.pattern, c.system, c.case_sensitive, c.json).await?;
+            }
+            ListCmd::Operators(c) => {
+                commands::list_operators(cli, &options,
+                    &c.pattern, c.system, c.case_sensitive, c.json).await?;
             }
             ListCmd::Indexes(c) => {
                 commands::list_indexes(cli, &options,
-                    &c.pattern, c.system, c.case_sensitive, c.verbose).await?;
+                    &c.pattern, c.system, c.case_sensitive, c.verbose,
+                    c.json).await?;
             }
-            ListCmd::Databases => {
-                commands::list_databases(cli, &options).await?;
+            ListCmd::Databases(c) => {
+                commands::list_databases(cli, &options,
+                    c.verbose, c.json, c.count_objects).await?;
             }
             ListCmd::Scalars(c) => {
                 commands::list_scalar_types(cli, &options,
-                    &c.pattern, c.system, c.case_sensitive).await?;
+                    &c.pattern, c.system, c.case_sensitive, c.json).await?;
             }
             ListCmd::Types(c) => {
                 commands::list_object_types(cli, &options,
-                    &c.pattern, c.system, c.case_sensitive).await?;
+                    &c.pattern, c.system, c.case_sensitive, c.json).await?;
             }
             ListCmd::Modules(c) => {
                 commands::list_modules(cli, &options,
-                    &c.pattern, c.case_sensitive).await?;
+                    &c.pattern, c.case_sensitive, c.json).await?;
             }
             ListCmd::Roles(c) => {
                 commands::list_roles(cli, &options,
-                    &c.pattern, c.case_sensitive).await?;
+                    &c.pattern, c.case_sensitive, c.json).await?;
             }
         }
         Analyze(c) => {
@@ -65,10 +101,11 @@ pub async fn common(cli: &mut Connection, cmd: &Common, options: &Options)
         }
         Describe(c) => match &c.subcommand {
             DescribeCmd::Object(c) => {
-                commands::describe(cli, &options, &c.name, c.verbose).await?;
+                commands::describe(cli, &options,
+                    &c.name, c.verbose, c.json).await?;
             }
-            DescribeCmd::Schema(_) => {
-                commands::describe_schema(cli, &options).await?;
+            DescribeCmd::Schema(c) => {
+                commands::describe_schema(cli, &options, c.json).await?;
             }
         },
         Dump(c) => {
@@ -78,6 +115,15 @@ pub async fn common(cli: &mut Connection, cmd: &Common, options: &Options)
             commands::restore(cli, &options, params)
             .await?;
         }
+        Export(c) => {
+            commands::export(cli, &options, c).await?;
+        }
+        Import(c) => {
+            commands::import(cli, &options, c).await?;
+        }
+        Load(c) => {
+            commands::load(cli, &options, c).await?;
+        }
         Configure(c) => {
             commands::configure(cli, &options, c).await?;
         }
@@ -91,6 +137,26 @@ pub async fn common(cli: &mut Connection, cmd: &Common, options: &Options)
             DatabaseCmd::Wipe(w) => {
                 commands::database::wipe(cli, w, &options).await?;
             }
+            DatabaseCmd::Rename(r) => {
+                commands::database::rename(cli, r, &options).await?;
+            }
+            DatabaseCmd::Clone(c) => {
+                commands::database::clone(cli, c, &options).await?;
+            }
+        }
+        Role(c) => match &c.subcommand {
+            RoleCmd::Create(c) => {
+                commands::role::create(cli, c, &options).await?;
+            }
+            RoleCmd::Alter(c) => {
+                commands::role::alter(cli, c, &options).await?;
+            }
+            RoleCmd::Drop(c) => {
+                commands::role::drop(cli, c, &options).await?;
+            }
+            RoleCmd::Show(c) => {
+                commands::role::show(cli, c, &options).await?;
+            }
         }
         Migrate(params) => {
             migrations::migrate(cli, &options, params).await?;
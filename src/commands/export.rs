@@ -0,0 +1,309 @@
+use std::path::Path;
+
+use anyhow::Context;
+use edgeql_parser::helpers::quote_name;
+use fn_error_context::context;
+use serde_json::Value;
+use tokio::fs;
+use tokio::io::{self, AsyncBufReadExt, AsyncWriteExt};
+use uuid::Uuid;
+
+use crate::commands::Options;
+use crate::commands::parser::Export;
+use crate::connect::Connection;
+use crate::hint::HintExt;
+use crate::platform::tmp_file_name;
+use crate::print;
+
+
+pub const MANIFEST: &str = "manifest.json";
+pub const FORMAT_VERSION: u32 = 1;
+pub const BATCH_SIZE: usize = 1000;
+
+/// Contents of `manifest.json`, describes the schema of the data files
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+pub struct Manifest {
+    pub format_version: u32,
+    pub server_version: String,
+    pub types: Vec<ObjectType>,
+    /// Names of the types whose data files are written completely
+    pub complete: Vec<String>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq)]
+pub struct ObjectType {
+    pub name: String,
+    pub ancestors: Vec<String>,
+    /// Data file, relative to the export directory
+    #[serde(default)]
+    pub file: String,
+    pub properties: Vec<Property>,
+    pub links: Vec<Link>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq)]
+pub struct Property {
+    pub name: String,
+    pub target: String,
+    pub multi: bool,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq)]
+pub struct Link {
+    pub name: String,
+    pub target: String,
+    pub multi: bool,
+    pub required: bool,
+    pub readonly: bool,
+    pub properties: Vec<Property>,
+}
+
+pub async fn export(cli: &mut Connection, _options: &Options, cmd: &Export)
+    -> anyhow::Result<()>
+{
+    let types = get_types(cli).await?;
+    let resume = fs::metadata(cmd.dir.join(MANIFEST)).await.is_ok();
+    let mut manifest = if resume {
+        if !cmd.resume {
+            return Err(anyhow::anyhow!("{} already contains an export",
+                                       cmd.dir.display()))
+                .hint("Use `--resume` to continue an interrupted export \
+                       or choose another directory")?;
+        }
+        let manifest = read_manifest(&cmd.dir).await?;
+        if manifest.types != types {
+            return Err(anyhow::anyhow!("schema has changed since \
+                                        the export was started"))
+                .hint("Remove the directory and export again")?;
+        }
+        manifest
+    } else {
+        fs::create_dir_all(&cmd.dir).await.with_context(|| {
+            format!("cannot create {}", cmd.dir.display())
+        })?;
+        let manifest = Manifest {
+            format_version: FORMAT_VERSION,
+            server_version: cli.get_version().await?.to_string(),
+            types,
+            complete: Vec::new(),
+        };
+        write_manifest(&cmd.dir, &manifest).await?;
+        manifest
+    };
+    for ty in manifest.types.clone() {
+        if manifest.complete.contains(&ty.name) {
+            continue;
+        }
+        let path = cmd.dir.join(&ty.file);
+        let count = export_type(cli, &path, &ty, resume).await
+            .with_context(|| format!("cannot export {}", ty.name))?;
+        print::success_msg("Exported",
+                           format!("{} ({} objects)", ty.name, count));
+        manifest.complete.push(ty.name);
+        write_manifest(&cmd.dir, &manifest).await?;
+    }
+    Ok(())
+}
+
+/// Quotes a type name for use in a query, names of collection and
+/// compound types are used verbatim
+pub fn type_expr(name: &str) -> String {
+    if name.contains(|c| matches!(c, '<' | '(' | '|' | '&')) {
+        return name.into();
+    }
+    name.split("::").map(quote_name).collect::<Vec<_>>().join("::")
+}
+
+async fn get_types(cli: &mut Connection) -> anyhow::Result<Vec<ObjectType>> {
+    let rows = cli.query::<String, _>(r###"
+        WITH MODULE schema
+        SELECT to_str(<json>(
+            SELECT ObjectType {
+                name,
+                ancestors := array_agg(.ancestors.name),
+                properties: {
+                    name,
+                    target := .target.name,
+                    multi := .cardinality = Cardinality.Many,
+                }
+                FILTER NOT EXISTS .expr AND .name != 'id',
+                links: {
+                    name,
+                    target := .target.name,
+                    multi := .cardinality = Cardinality.Many,
+                    required,
+                    readonly,
+                    properties: {
+                        name,
+                        target := .target.name,
+                        multi := .cardinality = Cardinality.Many,
+                    }
+                    FILTER NOT EXISTS .expr
+                        AND .name NOT IN {'source', 'target'},
+                }
+                FILTER NOT EXISTS .expr AND .name != '__type__',
+            }
+            FILTER NOT .builtin AND NOT .abstract
+                AND NOT .is_compound_type AND NOT .is_from_alias
+                AND NOT re_test(
+                    "^(?:std|schema|math|sys|cfg|cal|stdgraphql)::",
+                    .name)
+        ))
+    "###, &()).await?;
+    let mut types = Vec::with_capacity(rows.len());
+    for row in &rows {
+        let mut ty: ObjectType = serde_json::from_str(row)
+            .context("cannot decode schema")?;
+        // sorted to compare the schema when resuming
+        ty.ancestors.sort();
+        ty.properties.sort_by(|a, b| a.name.cmp(&b.name));
+        ty.links.sort_by(|a, b| a.name.cmp(&b.name));
+        for link in &mut ty.links {
+            link.properties.sort_by(|a, b| a.name.cmp(&b.name));
+        }
+        let name = ty.name.replace("::", ".");
+        ty.file = format!("{}.jsonl", urlencoding::encode(&name));
+        types.push(ty);
+    }
+    types.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(types)
+}
+
+/// Writes objects of the exact type `ty` (not of its subtypes) ordered by
+/// id, fetching them in batches. When resuming, the objects after the last
+/// complete line of the file are appended
+async fn export_type(cli: &mut Connection, path: &Path, ty: &ObjectType,
+                     resume: bool)
+    -> anyhow::Result<u64>
+{
+    let exists = fs::metadata(path).await.is_ok();
+    let (mut last_id, mut count, file) = if resume && exists {
+        let (offset, last_id, count) = scan_file(path).await?;
+        let file = fs::OpenOptions::new().append(true).open(path).await?;
+        file.set_len(offset).await?;
+        (last_id, count, file)
+    } else {
+        (Uuid::nil(), 0, fs::File::create(path).await?)
+    };
+    let mut out = io::BufWriter::new(file);
+    let query = format!(r###"
+        SELECT to_str(<json>(
+            SELECT {ty} {{ {shape} }}
+            FILTER .__type__.name = <str>$0 AND .id > <uuid>$1
+            ORDER BY .id
+            LIMIT <int64>$2
+        ))
+    "###, ty=type_expr(&ty.name), shape=shape(ty));
+    loop {
+        let rows = cli.query::<String, _>(
+            &query,
+            &(&ty.name[..], last_id, BATCH_SIZE as i64),
+        ).await?;
+        let mut objects = Vec::with_capacity(rows.len());
+        for row in &rows {
+            let mut object = serde_json::from_str(row)
+                .context("cannot decode object")?;
+            normalize(ty, &mut object);
+            objects.push((object_id(&object)?, object));
+        }
+        // ordering of the result set is not preserved by the cast to json
+        objects.sort_by_key(|(id, _)| *id);
+        for (id, object) in &objects {
+            out.write_all(serde_json::to_string(object)?.as_bytes()).await?;
+            out.write_all(b"\n").await?;
+            last_id = *id;
+        }
+        out.flush().await?;
+        count += objects.len() as u64;
+        if rows.len() < BATCH_SIZE {
+            break;
+        }
+    }
+    Ok(count)
+}
+
+fn shape(ty: &ObjectType) -> String {
+    let mut elements = vec![String::from("id")];
+    for prop in &ty.properties {
+        elements.push(quote_name(&prop.name).into_owned());
+    }
+    for link in &ty.links {
+        let mut inner = vec![String::from("id")];
+        for prop in &link.properties {
+            inner.push(format!("@{}", quote_name(&prop.name)));
+        }
+        elements.push(format!("{}: {{ {} }}",
+                              quote_name(&link.name), inner.join(", ")));
+    }
+    elements.join(", ")
+}
+
+/// Sorts values of multi properties and links, so that exports of the same
+/// data are identical
+fn normalize(ty: &ObjectType, object: &mut Value) {
+    for prop in ty.properties.iter().filter(|p| p.multi) {
+        if let Some(Value::Array(items)) = object.get_mut(&prop.name) {
+            items.sort_by_cached_key(|v| v.to_string());
+        }
+    }
+    for link in ty.links.iter().filter(|l| l.multi) {
+        if let Some(Value::Array(items)) = object.get_mut(&link.name) {
+            items.sort_by(|a, b| a["id"].as_str().cmp(&b["id"].as_str()));
+        }
+    }
+}
+
+fn object_id(object: &Value) -> anyhow::Result<Uuid> {
+    let id = object.get("id").and_then(|v| v.as_str())
+        .context("object has no id")?;
+    Ok(id.parse()?)
+}
+
+/// Returns the size of the complete lines in the file, the id of the last
+/// object and the number of objects. An incomplete last line is the result
+/// of an interrupted export
+async fn scan_file(path: &Path) -> anyhow::Result<(u64, Uuid, u64)> {
+    let mut input = io::BufReader::new(fs::File::open(path).await?);
+    let mut line = Vec::new();
+    let mut offset = 0;
+    let mut last_id = Uuid::nil();
+    let mut count = 0;
+    loop {
+        line.clear();
+        let len = input.read_until(b'\n', &mut line).await?;
+        if len == 0 || !line.ends_with(b"\n") {
+            break;
+        }
+        let object = serde_json::from_slice(&line).with_context(|| {
+            format!("{}: cannot decode line {}", path.display(), count + 1)
+        })?;
+        last_id = object_id(&object)?;
+        offset += len as u64;
+        count += 1;
+    }
+    Ok((offset, last_id, count))
+}
+
+pub async fn read_manifest(dir: &Path) -> anyhow::Result<Manifest> {
+    let path = dir.join(MANIFEST);
+    let data = fs::read(&path).await
+        .with_context(|| format!("cannot read {}", path.display()))?;
+    let manifest: Manifest = serde_json::from_slice(&data)
+        .with_context(|| format!("error decoding {}", path.display()))?;
+    if manifest.format_version != FORMAT_VERSION {
+        anyhow::bail!("unsupported export format version {}",
+                      manifest.format_version);
+    }
+    Ok(manifest)
+}
+
+#[context("cannot write {}", dir.join(MANIFEST).display())]
+async fn write_manifest(dir: &Path, manifest: &Manifest)
+    -> anyhow::Result<()>
+{
+    let path = dir.join(MANIFEST);
+    let tmp_path = path.with_file_name(tmp_file_name(&path));
+    fs::write(&tmp_path, serde_json::to_vec_pretty(manifest)?).await?;
+    fs::rename(&tmp_path, &path).await?;
+    Ok(())
+}
@@ -0,0 +1,244 @@
+use std::path::Path;
+
+use anyhow::Context;
+use edgeql_parser::helpers::{quote_name, quote_string};
+use tokio::fs;
+use tokio::io::{self, AsyncBufReadExt};
+
+use crate::commands::Options;
+use crate::commands::export::{read_manifest, type_expr, BATCH_SIZE};
+use crate::commands::export::{Link, ObjectType, Property};
+use crate::commands::parser::Import;
+use crate::connect::Connection;
+use crate::hint::HintExt;
+use crate::print;
+
+
+pub async fn import(cli: &mut Connection, _options: &Options, cmd: &Import)
+    -> anyhow::Result<()>
+{
+    let manifest = read_manifest(&cmd.dir).await?;
+    let incomplete = manifest.types.iter()
+        .filter(|ty| !manifest.complete.contains(&ty.name))
+        .map(|ty| &ty.name[..])
+        .collect::<Vec<_>>();
+    if !incomplete.is_empty() {
+        return Err(anyhow::anyhow!("export is incomplete, missing data of {}",
+                                   incomplete.join(", ")))
+            .hint("Run `edgedb export --resume` to finish it")?;
+    }
+    let types = insert_order(&manifest.types)?;
+    // the setting must not leak into the REPL session
+    let state = cli.get_state().clone();
+    let result = import_types(cli, &cmd.dir, &types).await;
+    cli.set_state(state);
+    result
+}
+
+async fn import_types(cli: &mut Connection, dir: &Path,
+                      types: &[&ObjectType])
+    -> anyhow::Result<()>
+{
+    cli.execute("CONFIGURE SESSION SET allow_user_specified_id := true", &())
+        .await?;
+    for ty in types {
+        let count = import_batches(cli, &dir.join(&ty.file), &insert_query(ty))
+            .await
+            .with_context(|| format!("cannot import {}", ty.name))?;
+        print::success_msg("Imported",
+                           format!("{} ({} objects)", ty.name, count));
+    }
+    // other links are set when all the objects exist, so they can form cycles
+    for ty in types {
+        let Some(query) = update_query(ty) else { continue };
+        import_batches(cli, &dir.join(&ty.file), &query).await
+            .with_context(|| format!("cannot import links of {}", ty.name))?;
+    }
+    Ok(())
+}
+
+/// Runs the query for each batch of lines of the data file, the batch is
+/// passed as a JSON array
+async fn import_batches(cli: &mut Connection, path: &Path, query: &str)
+    -> anyhow::Result<u64>
+{
+    let file = fs::File::open(path).await
+        .with_context(|| format!("cannot open {}", path.display()))?;
+    let mut lines = io::BufReader::new(file).lines();
+    let mut batch = Vec::with_capacity(BATCH_SIZE);
+    let mut count = 0;
+    while let Some(line) = lines.next_line().await? {
+        if line.trim().is_empty() {
+            continue;
+        }
+        batch.push(line);
+        if batch.len() >= BATCH_SIZE {
+            count += execute_batch(cli, query, &mut batch).await?;
+        }
+    }
+    if !batch.is_empty() {
+        count += execute_batch(cli, query, &mut batch).await?;
+    }
+    Ok(count)
+}
+
+async fn execute_batch(cli: &mut Connection, query: &str,
+                       batch: &mut Vec<String>)
+    -> anyhow::Result<u64>
+{
+    let data = format!("[{}]", batch.join(","));
+    cli.execute(query, &(data,)).await?;
+    let count = batch.len() as u64;
+    batch.clear();
+    Ok(count)
+}
+
+/// Links which have to be set by `INSERT`
+fn set_on_insert(link: &Link) -> bool {
+    link.required || link.readonly
+}
+
+/// Orders types so that targets of the links set on insert are imported
+/// before the types linking to them
+fn insert_order(types: &[ObjectType]) -> anyhow::Result<Vec<&ObjectType>> {
+    let depends = |ty: &ObjectType, other: &ObjectType| {
+        ty.links.iter().filter(|link| set_on_insert(link)).any(|link| {
+            link.target == other.name || other.ancestors.contains(&link.target)
+        })
+    };
+    let mut pending = types.iter().collect::<Vec<_>>();
+    let mut result = Vec::with_capacity(types.len());
+    while !pending.is_empty() {
+        let (ready, rest): (Vec<_>, Vec<_>) = pending.iter().copied()
+            .partition(|&ty| !pending.iter().any(|&other| depends(ty, other)));
+        if ready.is_empty() {
+            let names = rest.iter().map(|ty| &ty.name[..]).collect::<Vec<_>>();
+            anyhow::bail!("required links form a cycle between types: {}",
+                          names.join(", "));
+        }
+        result.extend(ready);
+        pending = rest;
+    }
+    Ok(result)
+}
+
+fn insert_query(ty: &ObjectType) -> String {
+    let mut elements = vec![String::from("id := <uuid>item['id']")];
+    for prop in &ty.properties {
+        elements.push(format!("{} := {}", quote_name(&prop.name),
+                              property_value(prop, "item", &prop.name)));
+    }
+    for link in ty.links.iter().filter(|link| set_on_insert(link)) {
+        elements.push(format!("{} := {}",
+                              quote_name(&link.name), link_value(link)));
+    }
+    format!(r###"
+        FOR item IN json_array_unpack(to_json(<str>$0)) UNION (
+            INSERT {ty} {{ {elements} }}
+        )
+    "###, ty=type_expr(&ty.name), elements=elements.join(", "))
+}
+
+fn update_query(ty: &ObjectType) -> Option<String> {
+    let elements = ty.links.iter()
+        .filter(|link| !set_on_insert(link))
+        .map(|link| format!("{} := {}",
+                            quote_name(&link.name), link_value(link)))
+        .collect::<Vec<_>>();
+    if elements.is_empty() {
+        return None;
+    }
+    Some(format!(r###"
+        FOR item IN json_array_unpack(to_json(<str>$0)) UNION (
+            UPDATE {ty} FILTER .id = <uuid>item['id']
+            SET {{ {elements} }}
+        )
+    "###, ty=type_expr(&ty.name), elements=elements.join(", ")))
+}
+
+/// Casts the value of `key` in the JSON object `source` to the property type
+fn property_value(prop: &Property, source: &str, key: &str) -> String {
+    let value = format!("json_get({}, {})", source, quote_string(key));
+    if prop.multi {
+        format!("array_unpack(<array<{}>>{})", type_expr(&prop.target), value)
+    } else {
+        format!("<{}>{}", type_expr(&prop.target), value)
+    }
+}
+
+/// Selects linked objects by the ids stored in `item`, with link properties
+fn link_value(link: &Link) -> String {
+    let shape = if link.properties.is_empty() {
+        String::new()
+    } else {
+        let props = link.properties.iter()
+            .map(|prop| format!("@{} := {}", quote_name(&prop.name),
+                                property_value(prop, "linked",
+                                               &format!("@{}", prop.name))))
+            .collect::<Vec<_>>();
+        format!(" {{ {} }}", props.join(", "))
+    };
+    let key = quote_string(&link.name);
+    let target = type_expr(&link.target);
+    if link.multi {
+        format!("(FOR linked IN json_array_unpack(json_get(item, {key})) \
+                 UNION (SELECT {target}{shape} \
+                        FILTER .id = <uuid>linked['id']))")
+    } else {
+        let value = format!("(WITH linked := json_get(item, {key}) \
+                              SELECT {target}{shape} \
+                              FILTER .id = <uuid>json_get(linked, 'id'))");
+        if link.required {
+            format!("assert_exists({})", value)
+        } else {
+            value
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::insert_order;
+    use crate::commands::export::{Link, ObjectType};
+
+    fn object_type(name: &str, ancestors: &[&str], links: &[(&str, bool)])
+        -> ObjectType
+    {
+        ObjectType {
+            name: name.into(),
+            ancestors: ancestors.iter().map(|a| a.to_string()).collect(),
+            file: String::new(),
+            properties: Vec::new(),
+            links: links.iter().map(|(target, required)| Link {
+                name: "link".into(),
+                target: target.to_string(),
+                multi: false,
+                required: *required,
+                readonly: false,
+                properties: Vec::new(),
+            }).collect(),
+        }
+    }
+
+    fn names(types: &[ObjectType]) -> Vec<&str> {
+        insert_order(types).unwrap().iter().map(|ty| &ty.name[..]).collect()
+    }
+
+    #[test]
+    fn order() {
+        let types = [
+            object_type("default::Post", &[], &[("default::Person", true)]),
+            object_type("default::Person", &["default::Named"],
+                        &[("default::Post", false)]),
+            object_type("default::Tag", &[], &[("default::Named", true)]),
+        ];
+        assert_eq!(names(&types),
+                   ["default::Person", "default::Post", "default::Tag"]);
+
+        let types = [
+            object_type("default::A", &[], &[("default::B", true)]),
+            object_type("default::B", &[], &[("default::A", true)]),
+        ];
+        assert!(insert_order(&types).is_err());
+    }
+}
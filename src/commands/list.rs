@@ -1,6 +1,13 @@
+use std::io::{stdout, Write};
+
 use crate::commands::Options;
 
 
+#[derive(serde::Serialize)]
+struct Named<'a> {
+    name: &'a str,
+}
+
 pub async fn print(items: impl IntoIterator<Item=String>,
                    title: &str, options: &Options)
     -> Result<(), anyhow::Error>
@@ -17,3 +24,38 @@ pub async fn print(items: impl IntoIterator<Item=String>,
     }
     Ok(())
 }
+
+/// Prints items as a pretty-printed JSON array
+///
+/// Field names of the items are a part of the public interface of the
+/// `--json` output, so they should not be changed.
+pub fn print_json<T: serde::Serialize + ?Sized>(items: &T)
+    -> Result<(), anyhow::Error>
+{
+    let mut out = serde_json::to_string_pretty(items)?;
+    out.push('\n');
+    stdout().lock().write_all(out.as_bytes())?;
+    Ok(())
+}
+
+/// Prints names as JSON array of objects: `[{"name": "..."}]`
+pub fn print_names_json(items: &[String]) -> Result<(), anyhow::Error> {
+    print_json(&items.iter()
+               .map(|name| Named { name })
+               .collect::<Vec<_>>())
+}
+
+/// Serializes list which is joined by `, ` in the query as an array
+pub fn comma_separated<S>(value: &str, serializer: S)
+    -> Result<S::Ok, S::Error>
+    where S: serde::Serializer
+{
+    use serde::ser::SerializeSeq;
+
+    let items = value.split(", ").filter(|x| !x.is_empty());
+    let mut seq = serializer.serialize_seq(None)?;
+    for item in items {
+        seq.serialize_element(item)?;
+    }
+    seq.end()
+}
@@ -5,21 +5,23 @@ use is_terminal::IsTerminal;
 
 use crate::commands::Options;
 use crate::commands::filter;
+use crate::commands::list;
 use crate::table;
 use crate::connect::Connection;
 
 
 
-#[derive(Queryable)]
+#[derive(Queryable, serde::Serialize)]
 struct Alias {
     name: String,
     expr: String,
+    #[serde(rename="class")]
     klass: String,
 }
 
 pub async fn list_aliases(cli: &mut Connection, options: &Options,
     pattern: &Option<String>, system: bool, case_sensitive: bool,
-    verbose: bool)
+    verbose: bool, json: bool)
     -> Result<(), anyhow::Error>
 {
     let filter = match (pattern, system) {
@@ -57,6 +59,9 @@ pub async fn list_aliases(cli: &mut Connection, options: &Options,
     "###, filter=filter);
     let items = filter::query::<Alias>(cli,
         &query, &pattern, case_sensitive).await?;
+    if json {
+        return list::print_json(&items);
+    }
     if !options.command_line || std::io::stdout().is_terminal() {
         let mut table = Table::new();
         table.set_format(*table::FORMAT);
@@ -5,21 +5,23 @@ use is_terminal::IsTerminal;
 
 use crate::commands::Options;
 use crate::commands::filter;
+use crate::commands::list;
 use crate::connect::Connection;
 use crate::table;
 
 
-#[derive(Queryable)]
+#[derive(Queryable, serde::Serialize)]
 struct Cast {
     from_type_name: String,
     to_type_name: String,
     kind: String,
+    #[serde(rename="volatility")]
     volatility_str: String,
 }
 
 
 pub async fn list_casts<'x>(cli: &mut Connection, options: &Options,
-    pattern: &Option<String>, case_sensitive: bool)
+    pattern: &Option<String>, case_sensitive: bool, json: bool)
     -> Result<(), anyhow::Error>
 {
     let filter = if pattern.is_some() {
@@ -46,6 +48,9 @@ pub async fn list_casts<'x>(cli: &mut Connection, options: &Options,
     "###, filter=filter);
     let items = filter::query::<Cast>(cli,
         &query, &pattern, case_sensitive).await?;
+    if json {
+        return list::print_json(&items);
+    }
     if !options.command_line || std::io::stdout().is_terminal() {
         let mut table = Table::new();
         table.set_format(*table::FORMAT);
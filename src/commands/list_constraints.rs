@@ -0,0 +1,117 @@
+use prettytable::{Table, Row, Cell};
+
+use edgedb_derive::Queryable;
+use is_terminal::IsTerminal;
+
+use crate::commands::Options;
+use crate::commands::filter;
+use crate::commands::list;
+use crate::commands::signature;
+use crate::connect::Connection;
+use crate::table;
+
+
+#[derive(Queryable)]
+struct Constraint {
+    name: String,
+    params_json: String,
+    return_type_name: String,
+    return_typemod: String,
+    expr: String,
+}
+
+#[derive(serde::Serialize)]
+struct ConstraintRow {
+    name: String,
+    signature: String,
+    params: Vec<signature::Param>,
+    return_type: String,
+    return_typemod: String,
+    expr: String,
+}
+
+pub async fn list_constraints(cli: &mut Connection, options: &Options,
+    pattern: &Option<String>, system: bool, case_sensitive: bool,
+    json: bool)
+    -> Result<(), anyhow::Error>
+{
+    let filter = match (pattern, system) {
+        (None, true) => "FILTER .abstract",
+        (None, false) => {
+            r#"FILTER .abstract AND NOT
+                re_test("^(?:std|schema|math|sys|cfg|cal|stdgraphql)::",
+                .name)"#
+        }
+        (Some(_), true) => {
+            "FILTER .abstract AND re_test(<str>$0, .name)"
+        }
+        (Some(_), false) => {
+            r#"FILTER .abstract
+                AND re_test(<str>$0, .name) AND
+                NOT re_test("^(?:std|schema|math|sys|cfg|cal|stdgraphql)::",
+                .name)"#
+        }
+    };
+    let query = &format!(r###"
+        WITH MODULE schema
+        SELECT Constraint {{
+            name,
+            params_json := {params},
+            return_type_name := .return_type.name ?? 'std::bool',
+            return_typemod := <str>.return_typemod ?? 'SingletonType',
+            expr := .expr ?? '',
+        }}
+        {filter}
+        ORDER BY .name;
+    "###, filter=filter, params=signature::PARAMS_JSON);
+    let items = filter::query::<Constraint>(cli,
+        &query, &pattern, case_sensitive).await?;
+    let mut rows = Vec::with_capacity(items.len());
+    for item in items {
+        let mut params = signature::parse_params(&item.params_json)?;
+        // subject is passed implicitly, it's never written by the user
+        params.retain(|p| p.name != "__subject__");
+        rows.push(ConstraintRow {
+            signature: signature::full(&params,
+                &item.return_typemod, &item.return_type_name),
+            name: item.name,
+            params,
+            return_type: item.return_type_name,
+            return_typemod: item.return_typemod,
+            expr: item.expr,
+        });
+    }
+    if json {
+        return list::print_json(&rows);
+    }
+    if !options.command_line || std::io::stdout().is_terminal() {
+        let mut table = Table::new();
+        table.set_format(*table::FORMAT);
+        table.set_titles(Row::new(
+            ["Name", "Signature", "Expression"]
+            .iter().map(|x| table::header_cell(x)).collect()));
+        for row in rows {
+            table.add_row(Row::new(vec![
+                Cell::new(&row.name),
+                Cell::new(&row.signature),
+                Cell::new(&row.expr),
+            ]));
+        }
+        if table.is_empty() {
+            if let Some(pattern) = pattern {
+                eprintln!("No constraints found matching {:?}", pattern);
+            } else if !system {
+                eprintln!("No user-defined constraints found. {}",
+                    if options.command_line { "Try --system" }
+                    else { r"Try \lC -s" });
+            }
+        } else {
+            table.printstd();
+        }
+    } else {
+        for row in rows {
+            println!("{}\t{}\t{}", row.name, row.signature, row.expr);
+        }
+    }
+    Ok(())
+}
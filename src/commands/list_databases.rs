@@ -1,7 +1,47 @@
+use std::collections::BTreeMap;
+
+use anyhow::Context;
+use edgedb_tokio::server_params::PostgresAddress;
+use indicatif::HumanBytes;
+use is_terminal::IsTerminal;
+use prettytable::{Table, Row, Cell};
+use tokio::process::Command;
+
 use crate::commands::Options;
 use crate::commands::list;
+use crate::commands::psql::psql_path;
 use crate::connect::Connection;
+use crate::portable::project;
+use crate::table;
+
+
+#[derive(serde::Deserialize, Debug)]
+struct Stats {
+    object_types: i64,
+    objects: Option<i64>,
+    last_migration: Option<String>,
+}
+
+#[derive(serde::Serialize, Debug)]
+struct DatabaseInfo {
+    name: String,
+    /// On-disk size in bytes, only known for dev-mode servers
+    size: Option<u64>,
+    object_types: Option<i64>,
+    /// Only counted with `--count-objects`
+    objects: Option<i64>,
+    last_migration: Option<String>,
+    /// Creation time, only known for dev-mode servers
+    created: Option<String>,
+    /// Database is the one the project in the current directory uses
+    project: bool,
+}
 
+/// Size and creation time of the backend database
+struct Storage {
+    size: Option<u64>,
+    created: Option<String>,
+}
 
 pub async fn get_databases(cli: &mut Connection) -> anyhow::Result<Vec<String>>
 {
@@ -12,10 +52,162 @@ pub async fn get_databases(cli: &mut Connection) -> anyhow::Result<Vec<String>>
     Ok(databases)
 }
 
-pub async fn list_databases(cli: &mut Connection, options: &Options)
+pub async fn list_databases(cli: &mut Connection, options: &Options,
+    verbose: bool, json: bool, count_objects: bool)
     -> Result<(), anyhow::Error>
 {
     let databases = get_databases(cli).await?;
-    list::print(databases, "List of databases", options).await?;
+    if !verbose && !json {
+        list::print(databases, "List of databases", options).await?;
+        return Ok(());
+    }
+    let config = options.conn_params.get()?;
+    let linked = match config.instance_name() {
+        Some(instance) => project::linked_database(instance)
+            .map_err(|e| log::warn!("Cannot read project info: {:#}", e))
+            .ok().flatten(),
+        None => None,
+    };
+    let mut storage = backend_storage(cli).await.unwrap_or_else(|e| {
+        log::warn!("Cannot fetch database sizes: {:#}", e);
+        BTreeMap::new()
+    });
+
+    let mut conn_params = options.conn_params.clone();
+    let mut items = Vec::with_capacity(databases.len());
+    for name in databases {
+        let stats = if name == cli.database() {
+            fetch_stats(cli, count_objects).await
+        } else {
+            match conn_params.database(&name)?.connect().await {
+                Ok(mut conn) => fetch_stats(&mut conn, count_objects).await,
+                Err(e) => Err(e),
+            }
+        };
+        let stats = stats.map_err(|e| {
+            log::warn!("Cannot fetch statistics of {:?}: {:#}", name, e);
+        }).ok();
+        let backend = storage.remove(&name);
+        items.push(DatabaseInfo {
+            size: backend.as_ref().and_then(|s| s.size),
+            created: backend.and_then(|s| s.created),
+            object_types: stats.as_ref().map(|s| s.object_types),
+            objects: stats.as_ref().and_then(|s| s.objects),
+            last_migration: stats.and_then(|s| s.last_migration),
+            project: linked.as_ref() == Some(&name),
+            name,
+        });
+    }
+    if json {
+        return list::print_json(&items);
+    }
+
+    let size = |item: &DatabaseInfo| {
+        item.size.map(|s| HumanBytes(s).to_string()).unwrap_or_default()
+    };
+    let number = |value: Option<i64>| {
+        value.map(|v| v.to_string()).unwrap_or_default()
+    };
+    if !options.command_line || std::io::stdout().is_terminal() {
+        let mut table = Table::new();
+        table.set_format(*table::FORMAT);
+        table.set_titles(Row::new(
+            ["Name", "Size", "Types", "Objects", "Last Migration", "Created",
+             "Project"]
+            .iter().map(|x| table::header_cell(x)).collect()));
+        for item in &items {
+            table.add_row(Row::new(vec![
+                Cell::new(&item.name),
+                Cell::new(&size(item)),
+                Cell::new(&number(item.object_types)),
+                Cell::new(&number(item.objects)),
+                Cell::new(item.last_migration.as_deref().unwrap_or("")),
+                Cell::new(item.created.as_deref().unwrap_or("")),
+                Cell::new(if item.project { "yes" } else { "" }),
+            ]));
+        }
+        if table.is_empty() {
+            eprintln!("No databases found.");
+        } else {
+            table.printstd();
+        }
+    } else {
+        for item in &items {
+            println!("{}\t{}\t{}\t{}\t{}\t{}\t{}",
+                item.name,
+                item.size.map(|s| s.to_string()).unwrap_or_default(),
+                number(item.object_types),
+                number(item.objects),
+                item.last_migration.as_deref().unwrap_or(""),
+                item.created.as_deref().unwrap_or(""),
+                item.project);
+        }
+    }
     Ok(())
 }
+
+async fn fetch_stats(cli: &mut Connection, count_objects: bool)
+    -> anyhow::Result<Stats>
+{
+    // counting objects scans all the data in the database
+    let data = cli.query_required_single::<String, _>(r###"
+        SELECT to_str(<json>{
+            object_types := count(
+                (SELECT schema::ObjectType FILTER NOT .builtin)
+            ),
+            objects := count(std::Object) IF <bool>$0 ELSE <int64>{},
+            last_migration := (
+                SELECT schema::Migration
+                FILTER .id NOT IN schema::Migration.parents.id
+                LIMIT 1
+            ).name,
+        })
+    "###, &(count_objects,)).await?;
+    Ok(serde_json::from_str(&data).context("cannot decode statistics")?)
+}
+
+/// Queries sizes of the backend databases using `psql`, only works on
+/// dev-mode servers that expose their Postgres address
+async fn backend_storage(cli: &Connection)
+    -> anyhow::Result<BTreeMap<String, Storage>>
+{
+    let Some(addr) = cli.get_server_param::<PostgresAddress>() else {
+        return Ok(BTreeMap::new());
+    };
+    // backend database names are prefixed by the tenant id
+    let prefix = addr.database.strip_suffix(cli.database()).unwrap_or("");
+    let output = Command::new("psql")
+        .arg("-h").arg(&addr.host)
+        .arg("-U").arg(&addr.user)
+        .arg("-p").arg(addr.port.to_string())
+        .arg("-d").arg(&addr.database)
+        // never prompt for a password, sizes are just not shown then
+        .arg("-w")
+        .arg("--no-align").arg("--tuples-only").arg("--field-separator=\t")
+        .arg("-c").arg(r###"
+            SELECT datname, pg_database_size(oid),
+                (pg_stat_file('base/' || oid || '/PG_VERSION')).modification
+            FROM pg_database
+        "###)
+        .envs(psql_path().map(|path| ("PATH", path)))
+        .output()
+        .await
+        .context("cannot run psql")?;
+    if !output.status.success() {
+        anyhow::bail!("psql failed: {}",
+                      String::from_utf8_lossy(&output.stderr).trim());
+    }
+    let mut result = BTreeMap::new();
+    for line in String::from_utf8_lossy(&output.stdout).lines() {
+        let mut fields = line.split('\t');
+        let (Some(datname), Some(size), created) =
+            (fields.next(), fields.next(), fields.next())
+            else { continue };
+        let Some(name) = datname.strip_prefix(prefix) else { continue };
+        result.insert(name.to_string(), Storage {
+            size: size.parse().ok(),
+            created: created.map(|c| c.to_string()).filter(|c| !c.is_empty()),
+        });
+    }
+    Ok(result)
+}
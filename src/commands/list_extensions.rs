@@ -0,0 +1,77 @@
+use prettytable::{Table, Row, Cell};
+
+use edgedb_derive::Queryable;
+use is_terminal::IsTerminal;
+
+use crate::commands::Options;
+use crate::commands::filter;
+use crate::commands::list;
+use crate::connect::Connection;
+use crate::table;
+
+
+#[derive(Queryable, serde::Serialize)]
+struct Extension {
+    name: String,
+    version: String,
+}
+
+pub async fn list_extensions(cli: &mut Connection, options: &Options,
+    pattern: &Option<String>, case_sensitive: bool, json: bool)
+    -> Result<(), anyhow::Error>
+{
+    let filter = if pattern.is_some() {
+        "FILTER re_test(<str>$0, .name)"
+    } else {
+        ""
+    };
+    let query = &format!(r###"
+        WITH MODULE schema
+        SELECT Extension {{
+            name,
+            version := (
+                WITH v := .package.version
+                SELECT
+                    <str>v.major ++ '.' ++ <str>v.minor
+                    ++ (
+                        '' IF <str>v.stage = 'final'
+                        ELSE '-' ++ <str>v.stage ++ '.' ++ <str>v.stage_no
+                    )
+            ) ?? '',
+        }}
+        {filter}
+        ORDER BY .name;
+    "###, filter=filter);
+    let items = filter::query::<Extension>(cli,
+        &query, &pattern, case_sensitive).await?;
+    if json {
+        return list::print_json(&items);
+    }
+    if !options.command_line || std::io::stdout().is_terminal() {
+        let mut table = Table::new();
+        table.set_format(*table::FORMAT);
+        table.set_titles(Row::new(
+            ["Name", "Version"]
+            .iter().map(|x| table::header_cell(x)).collect()));
+        for item in items {
+            table.add_row(Row::new(vec![
+                Cell::new(&item.name),
+                Cell::new(&item.version),
+            ]));
+        }
+        if table.is_empty() {
+            if let Some(pattern) = pattern {
+                eprintln!("No extensions found matching {:?}", pattern);
+            } else {
+                eprintln!("No extensions installed.");
+            }
+        } else {
+            table.printstd();
+        }
+    } else {
+        for item in items {
+            println!("{}\t{}", item.name, item.version);
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,112 @@
+use prettytable::{Table, Row, Cell};
+
+use edgedb_derive::Queryable;
+use is_terminal::IsTerminal;
+
+use crate::commands::Options;
+use crate::commands::filter;
+use crate::commands::list;
+use crate::commands::signature;
+use crate::connect::Connection;
+use crate::table;
+
+
+#[derive(Queryable)]
+struct Function {
+    name: String,
+    params_json: String,
+    return_type_name: String,
+    return_typemod: String,
+    volatility_str: String,
+}
+
+#[derive(serde::Serialize)]
+struct FunctionRow {
+    name: String,
+    signature: String,
+    params: Vec<signature::Param>,
+    return_type: String,
+    return_typemod: String,
+    volatility: String,
+}
+
+pub async fn list_functions(cli: &mut Connection, options: &Options,
+    pattern: &Option<String>, system: bool, case_sensitive: bool,
+    json: bool)
+    -> Result<(), anyhow::Error>
+{
+    let filter = match (pattern, system) {
+        (None, true) => "",
+        (None, false) => {
+            r#"FILTER NOT
+                re_test("^(?:std|schema|math|sys|cfg|cal|stdgraphql)::",
+                .name)"#
+        }
+        (Some(_), true) => "FILTER re_test(<str>$0, .name)",
+        (Some(_), false) => {
+            r#"FILTER re_test(<str>$0, .name) AND
+                NOT re_test("^(?:std|schema|math|sys|cfg|cal|stdgraphql)::",
+                .name)"#
+        }
+    };
+    let query = &format!(r###"
+        WITH MODULE schema
+        SELECT Function {{
+            name,
+            params_json := {params},
+            return_type_name := .return_type.name,
+            return_typemod := <str>.return_typemod,
+            volatility_str := <str>.volatility,
+        }}
+        {filter}
+        ORDER BY .name;
+    "###, filter=filter, params=signature::PARAMS_JSON);
+    let items = filter::query::<Function>(cli,
+        &query, &pattern, case_sensitive).await?;
+    let mut rows = Vec::with_capacity(items.len());
+    for item in items {
+        let params = signature::parse_params(&item.params_json)?;
+        rows.push(FunctionRow {
+            signature: signature::full(&params,
+                &item.return_typemod, &item.return_type_name),
+            name: item.name,
+            params,
+            return_type: item.return_type_name,
+            return_typemod: item.return_typemod,
+            volatility: item.volatility_str,
+        });
+    }
+    if json {
+        return list::print_json(&rows);
+    }
+    if !options.command_line || std::io::stdout().is_terminal() {
+        let mut table = Table::new();
+        table.set_format(*table::FORMAT);
+        table.set_titles(Row::new(
+            ["Name", "Signature", "Volatility"]
+            .iter().map(|x| table::header_cell(x)).collect()));
+        for row in rows {
+            table.add_row(Row::new(vec![
+                Cell::new(&row.name),
+                Cell::new(&row.signature),
+                Cell::new(&row.volatility),
+            ]));
+        }
+        if table.is_empty() {
+            if let Some(pattern) = pattern {
+                eprintln!("No functions found matching {:?}", pattern);
+            } else if !system {
+                eprintln!("No user-defined functions found. {}",
+                    if options.command_line { "Try --system" }
+                    else { r"Try \lf -s" });
+            }
+        } else {
+            table.printstd();
+        }
+    } else {
+        for row in rows {
+            println!("{}\t{}\t{}", row.name, row.signature, row.volatility);
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,165 @@
+use prettytable::{Table, Row, Cell};
+
+use edgedb_derive::Queryable;
+use is_terminal::IsTerminal;
+
+use crate::commands::Options;
+use crate::commands::filter;
+use crate::commands::list;
+use crate::commands::helpers::quote_namespaced;
+use crate::connect::Connection;
+use crate::table;
+
+
+#[derive(Queryable)]
+struct Global {
+    name: String,
+    target_name: String,
+    required: bool,
+    multi: bool,
+    default_expr: String,
+    computed_expr: String,
+}
+
+#[derive(serde::Serialize)]
+struct GlobalJson<'a> {
+    name: &'a str,
+    #[serde(rename="type")]
+    target_name: &'a str,
+    required: bool,
+    multi: bool,
+    default: Option<&'a str>,
+    computed: Option<&'a str>,
+    /// Current session value as shown in the table (only in the REPL)
+    #[serde(skip_serializing_if="Option::is_none")]
+    value: Option<&'a str>,
+}
+
+pub async fn list_globals(cli: &mut Connection, options: &Options,
+    pattern: &Option<String>, case_sensitive: bool, json: bool)
+    -> Result<(), anyhow::Error>
+{
+    let filter = if pattern.is_some() {
+        "FILTER re_test(<str>$0, .name)"
+    } else {
+        ""
+    };
+    let query = &format!(r###"
+        WITH MODULE schema
+        SELECT Global {{
+            name,
+            target_name := .target.name,
+            required := .required ?? false,
+            multi := (<str>.cardinality ?? '') = 'Many',
+            default_expr := .default ?? '',
+            computed_expr := .expr ?? '',
+        }}
+        {filter}
+        ORDER BY .name;
+    "###, filter=filter);
+    let items = filter::query::<Global>(cli,
+        &query, &pattern, case_sensitive).await?;
+    // Session state is only kept by the REPL, in command-line mode
+    // the value of a global is always its default
+    let show_value = !options.command_line;
+    let mut values = Vec::with_capacity(items.len());
+    if show_value {
+        for item in &items {
+            values.push(current_value(cli, item).await);
+        }
+    }
+    if json {
+        let items = items.iter().enumerate().map(|(idx, item)| GlobalJson {
+            name: &item.name,
+            target_name: &item.target_name,
+            required: item.required,
+            multi: item.multi,
+            default: non_empty(&item.default_expr),
+            computed: non_empty(&item.computed_expr),
+            value: values.get(idx).map(|v| &v[..]),
+        }).collect::<Vec<_>>();
+        return list::print_json(&items);
+    }
+    if !options.command_line || std::io::stdout().is_terminal() {
+        let mut table = Table::new();
+        table.set_format(*table::FORMAT);
+        let mut titles = vec!["Name", "Type", "Default"];
+        if show_value {
+            titles.push("Value");
+        }
+        table.set_titles(Row::new(
+            titles.iter().map(|x| table::header_cell(x)).collect()));
+        for (idx, item) in items.iter().enumerate() {
+            let mut row = vec![
+                Cell::new(&item.name),
+                Cell::new(&type_str(item)),
+                Cell::new(&default_str(item)),
+            ];
+            if let Some(value) = values.get(idx) {
+                row.push(Cell::new(value));
+            }
+            table.add_row(Row::new(row));
+        }
+        if table.is_empty() {
+            if let Some(pattern) = pattern {
+                eprintln!("No globals found matching {:?}", pattern);
+            } else {
+                eprintln!("No globals found.");
+            }
+        } else {
+            table.printstd();
+        }
+    } else {
+        for item in items {
+            println!("{}\t{}\t{}",
+                item.name, type_str(&item), default_str(&item));
+        }
+    }
+    Ok(())
+}
+
+fn non_empty(value: &str) -> Option<&str> {
+    if value.is_empty() {
+        None
+    } else {
+        Some(value)
+    }
+}
+
+fn type_str(item: &Global) -> String {
+    let mut buf = String::new();
+    if item.required {
+        buf.push_str("required ");
+    }
+    if item.multi {
+        buf.push_str("multi ");
+    }
+    buf.push_str(&item.target_name);
+    buf
+}
+
+fn default_str(item: &Global) -> String {
+    if !item.computed_expr.is_empty() {
+        format!(":= {}", item.computed_expr)
+    } else {
+        item.default_expr.clone()
+    }
+}
+
+async fn current_value(cli: &mut Connection, item: &Global) -> String {
+    let name = quote_namespaced(&item.name);
+    let query = if item.multi {
+        format!("SELECT to_str(<json>array_agg(GLOBAL {name}))")
+    } else {
+        format!("SELECT to_str(<json>(GLOBAL {name}))")
+    };
+    match cli.query_single::<String, _>(&query, &()).await {
+        Ok(Some(value)) => value,
+        Ok(None) => String::from("{}"),
+        Err(e) => {
+            log::debug!("Cannot fetch value of global {}: {:#}",
+                        item.name, e);
+            String::from("?")
+        }
+    }
+}
@@ -5,22 +5,23 @@ use is_terminal::IsTerminal;
 
 use crate::commands::Options;
 use crate::commands::filter;
+use crate::commands::list;
 use crate::connect::Connection;
 use crate::table;
 
 
 
-#[derive(Queryable)]
-struct Index {
-    expr: String,
-    is_implicit: bool,
-    subject_name: String,
+#[derive(Queryable, serde::Serialize)]
+pub struct Index {
+    pub expr: String,
+    pub is_implicit: bool,
+    pub subject_name: String,
 }
 
-pub async fn list_indexes(cli: &mut Connection, options: &Options,
+pub async fn get_indexes(cli: &mut Connection,
     pattern: &Option<String>, system: bool, case_sensitive: bool,
     verbose: bool)
-    -> Result<(), anyhow::Error>
+    -> Result<Vec<Index>, anyhow::Error>
 {
     let mut filters = Vec::with_capacity(3);
     if !system {
@@ -70,6 +71,19 @@ pub async fn list_indexes(cli: &mut Connection, options: &Options,
     "###, filter=filter);
     let items = filter::query::<Index>(cli,
         &query, &pattern, case_sensitive).await?;
+    Ok(items)
+}
+
+pub async fn list_indexes(cli: &mut Connection, options: &Options,
+    pattern: &Option<String>, system: bool, case_sensitive: bool,
+    verbose: bool, json: bool)
+    -> Result<(), anyhow::Error>
+{
+    let items = get_indexes(cli,
+        pattern, system, case_sensitive, verbose).await?;
+    if json {
+        return list::print_json(&items);
+    }
     if !options.command_line || std::io::stdout().is_terminal() {
         let mut table = Table::new();
         table.set_format(*table::FORMAT);
@@ -0,0 +1,197 @@
+use prettytable::{Table, Row, Cell};
+
+use edgedb_derive::Queryable;
+use is_terminal::IsTerminal;
+
+use crate::commands::Options;
+use crate::commands::filter;
+use crate::commands::list;
+use crate::connect::Connection;
+use crate::print;
+use crate::table;
+
+
+#[derive(Queryable, serde::Serialize)]
+struct Policy {
+    subject_name: String,
+    name: String,
+    action: String,
+    #[serde(serialize_with="list::comma_separated")]
+    access_kinds: String,
+    condition: String,
+    expr: String,
+}
+
+#[derive(Queryable, serde::Serialize)]
+struct Trigger {
+    subject_name: String,
+    name: String,
+    timing: String,
+    #[serde(serialize_with="list::comma_separated")]
+    kinds: String,
+    scope: String,
+    expr: String,
+}
+
+#[derive(Queryable, serde::Serialize)]
+struct Rewrite {
+    subject_name: String,
+    pointer_name: String,
+    kind: String,
+    expr: String,
+}
+
+/// Pattern is matched against both the object type and the item name, so
+/// that `\lp User` shows every policy that applies to `default::User`
+const PATTERN_FILTER: &str =
+    "FILTER re_test(<str>$0, .subject_name) OR re_test(<str>$0, .name)";
+
+pub async fn list_policies(cli: &mut Connection, options: &Options,
+    pattern: &Option<String>, case_sensitive: bool, json: bool)
+    -> Result<(), anyhow::Error>
+{
+    let query = &format!(r###"
+        WITH MODULE schema
+        SELECT AccessPolicy {{
+            subject_name := .subject.name,
+            name,
+            action := <str>.action,
+            access_kinds := to_str(array_agg(<str>.access_kinds), ', '),
+            condition := .condition ?? '',
+            expr := .expr ?? '',
+        }}
+        {filter}
+        ORDER BY .subject_name THEN .name;
+    "###, filter=if pattern.is_some() { PATTERN_FILTER } else { "" });
+    let items = filter::query::<Policy>(cli,
+        &query, &pattern, case_sensitive).await?;
+    if json {
+        return list::print_json(&items);
+    }
+    let rows = items.into_iter()
+        .map(|p| vec![p.subject_name, p.name, p.action, p.access_kinds,
+                      p.condition, p.expr])
+        .collect();
+    print_grouped(options, "access policies", pattern,
+        &["Type", "Name", "Action", "Access", "When", "Using"], rows);
+    Ok(())
+}
+
+pub async fn list_triggers(cli: &mut Connection, options: &Options,
+    pattern: &Option<String>, case_sensitive: bool, json: bool)
+    -> Result<(), anyhow::Error>
+{
+    let query = &format!(r###"
+        WITH MODULE schema
+        SELECT Trigger {{
+            subject_name := .subject.name,
+            name,
+            timing := <str>.timing,
+            kinds := to_str(array_agg(<str>.kinds), ', '),
+            scope := <str>.scope,
+            expr := .expr ?? '',
+        }}
+        {filter}
+        ORDER BY .subject_name THEN .name;
+    "###, filter=if pattern.is_some() { PATTERN_FILTER } else { "" });
+    let items = if supported(cli, "Triggers").await? {
+        filter::query::<Trigger>(cli, &query, &pattern, case_sensitive).await?
+    } else {
+        Vec::new()
+    };
+    if json {
+        return list::print_json(&items);
+    }
+    let rows = items.into_iter()
+        .map(|t| vec![t.subject_name, t.name, t.timing, t.kinds,
+                      t.scope, t.expr])
+        .collect();
+    print_grouped(options, "triggers", pattern,
+        &["Type", "Name", "Timing", "Kinds", "Scope", "Expression"], rows);
+    Ok(())
+}
+
+pub async fn list_rewrites(cli: &mut Connection, options: &Options,
+    pattern: &Option<String>, case_sensitive: bool, json: bool)
+    -> Result<(), anyhow::Error>
+{
+    let query = &format!(r###"
+        WITH MODULE schema
+        SELECT Rewrite {{
+            subject_name := .subject.source.name,
+            pointer_name := .subject.name,
+            kind := <str>.kind,
+            expr := .expr ?? '',
+        }}
+        {filter}
+        ORDER BY .subject_name THEN .pointer_name THEN .kind;
+    "###, filter=if pattern.is_some() {
+        "FILTER re_test(<str>$0, .subject_name) \
+         OR re_test(<str>$0, .pointer_name)"
+    } else {
+        ""
+    });
+    let items = if supported(cli, "Rewrites").await? {
+        filter::query::<Rewrite>(cli, &query, &pattern, case_sensitive).await?
+    } else {
+        Vec::new()
+    };
+    if json {
+        return list::print_json(&items);
+    }
+    let rows = items.into_iter()
+        .map(|r| vec![r.subject_name, r.pointer_name, r.kind, r.expr])
+        .collect();
+    print_grouped(options, "rewrites", pattern,
+        &["Type", "Pointer", "Kind", "Expression"], rows);
+    Ok(())
+}
+
+/// Triggers and rewrites are added in EdgeDB 4.0, the listing is empty
+/// on older servers
+async fn supported(cli: &mut Connection, title: &str) -> anyhow::Result<bool>
+{
+    if cli.get_version().await?.specific() < "4.0-alpha.1".parse().unwrap() {
+        print::warn(format!("{} are only supported in EdgeDB >= 4.0", title));
+        return Ok(false);
+    }
+    Ok(true)
+}
+
+/// Prints rows sorted by object type (first column), in a table the type
+/// name is shown only once for each group
+fn print_grouped(options: &Options, title: &str, pattern: &Option<String>,
+                 titles: &[&str], rows: Vec<Vec<String>>)
+{
+    if !options.command_line || std::io::stdout().is_terminal() {
+        let mut table = Table::new();
+        table.set_format(*table::FORMAT);
+        table.set_titles(Row::new(
+            titles.iter().map(|x| table::header_cell(x)).collect()));
+        let mut last_type = None;
+        for row in &rows {
+            let mut cells = Vec::with_capacity(row.len());
+            if last_type == Some(&row[0]) {
+                cells.push(Cell::new(""));
+            } else {
+                cells.push(Cell::new(&row[0]));
+            }
+            last_type = Some(&row[0]);
+            cells.extend(row[1..].iter().map(|x| Cell::new(x)));
+            table.add_row(Row::new(cells));
+        }
+        if table.is_empty() {
+            if let Some(pattern) = pattern {
+                eprintln!("No {} found matching {:?}", title, pattern);
+            } else {
+                eprintln!("No {} found.", title);
+            }
+        } else {
+            table.printstd();
+        }
+    } else {
+        for row in rows {
+            println!("{}", row.join("\t"));
+        }
+    }
+}
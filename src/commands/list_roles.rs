@@ -5,7 +5,7 @@ use crate::connect::Connection;
 
 
 pub async fn list_roles<'x>(cli: &mut Connection, options: &Options,
-    pattern: &Option<String>, case_sensitive: bool)
+    pattern: &Option<String>, case_sensitive: bool, json: bool)
     -> Result<(), anyhow::Error>
 {
     let filter = if pattern.is_some() {
@@ -18,7 +18,11 @@ pub async fn list_roles<'x>(cli: &mut Connection, options: &Options,
         {filter}
         ORDER BY name
     "###, filter=filter);
-    let items = filter::query(cli, &query, &pattern, case_sensitive).await?;
+    let items = filter::query::<String>(cli,
+        &query, &pattern, case_sensitive).await?;
+    if json {
+        return list::print_names_json(&items);
+    }
     list::print(items, "List of roles", options).await?;
     Ok(())
 }
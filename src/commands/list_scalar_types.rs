@@ -6,20 +6,23 @@ use terminal_size::{Width, terminal_size};
 
 use crate::commands::Options;
 use crate::commands::filter;
+use crate::commands::list;
 use crate::connect::Connection;
 use crate::table;
 
 
 
-#[derive(Queryable)]
+#[derive(Queryable, serde::Serialize)]
 struct ScalarType {
     name: String,
+    #[serde(serialize_with="list::comma_separated")]
     extending: String,
     kind: String,
 }
 
 pub async fn list_scalar_types<'x>(cli: &mut Connection, options: &Options,
-    pattern: &Option<String>, system: bool, case_sensitive: bool)
+    pattern: &Option<String>, system: bool, case_sensitive: bool,
+    json: bool)
     -> Result<(), anyhow::Error>
 {
     let filter = match (pattern, system) {
@@ -57,6 +60,9 @@ pub async fn list_scalar_types<'x>(cli: &mut Connection, options: &Options,
 
     let items = filter::query::<ScalarType>(cli,
         &query, &pattern, case_sensitive).await?;
+    if json {
+        return list::print_json(&items);
+    }
     if !options.command_line || std::io::stdout().is_terminal() {
         let term_width = terminal_size()
             .map(|(Width(w), _h)| w).unwrap_or(80);
@@ -0,0 +1,629 @@
+use std::collections::{BTreeMap, HashSet};
+use std::io::{BufRead, BufReader};
+use std::mem;
+use std::path::Path;
+use std::slice;
+
+use anyhow::Context;
+use edgedb_errors::{ClientError, Error};
+use edgeql_parser::helpers::{quote_name, quote_string};
+use indicatif::{ProgressBar, ProgressStyle};
+use serde_json::{Map, Value};
+use tokio::fs;
+use tokio::io::{self, AsyncWriteExt};
+use tokio::sync::mpsc;
+use tokio::task::spawn_blocking as unblock;
+
+use crate::commands::Options;
+use crate::commands::export::type_expr;
+use crate::commands::parser::{Load, LoadFormat, OnError};
+use crate::connect::Connection;
+use crate::hint::HintExt;
+use crate::print;
+
+
+#[derive(serde::Deserialize, Debug)]
+struct TypeInfo {
+    properties: Vec<PropertyInfo>,
+    links: Vec<LinkInfo>,
+}
+
+#[derive(serde::Deserialize, Debug)]
+struct PropertyInfo {
+    name: String,
+    target: String,
+    multi: bool,
+}
+
+#[derive(serde::Deserialize, Debug)]
+struct LinkInfo {
+    name: String,
+    target: String,
+}
+
+/// Property of the link target used to find the linked object
+#[derive(serde::Deserialize, Debug)]
+struct KeyInfo {
+    target: String,
+    exclusive: bool,
+}
+
+#[derive(Debug)]
+struct Lookup {
+    link: String,
+    target: String,
+    property: String,
+    property_type: String,
+}
+
+/// Pointers the columns of the file are loaded into
+struct Mapping {
+    properties: BTreeMap<String, String>,
+    links: BTreeMap<String, String>,
+    /// Columns without a mapping are loaded into properties of the same name
+    infer: bool,
+}
+
+/// Number of rows parsed ahead of the ones being inserted
+const READ_AHEAD: usize = 1024;
+
+/// Item parsed from the input file by the reading thread
+enum Input {
+    /// Columns of the CSV header
+    Header(Vec<String>),
+    Row { line: u64, data: Map<String, Value>, position: u64 },
+    Invalid { line: u64, error: String, position: u64 },
+}
+
+struct Row {
+    line: u64,
+    data: Map<String, Value>,
+    /// Values keyed by pointer names, passed to the insert statement
+    item: Map<String, Value>,
+}
+
+#[derive(serde::Serialize)]
+struct RejectedRow<'a> {
+    line: u64,
+    error: &'a str,
+    row: &'a Map<String, Value>,
+}
+
+struct Loader<'a> {
+    cli: &'a mut Connection,
+    cmd: &'a Load,
+    type_name: String,
+    info: TypeInfo,
+    mapping: Mapping,
+    lookups: Vec<Lookup>,
+    format: LoadFormat,
+    batch: Vec<Row>,
+    /// Pointers set by the rows in the batch, statement depends on them
+    pointers: Vec<String>,
+    rejected_file: Option<io::BufWriter<fs::File>>,
+    bar: ProgressBar,
+    inserted: u64,
+    duplicates: u64,
+    rejected: u64,
+}
+
+pub async fn load(cli: &mut Connection, _options: &Options, cmd: &Load)
+    -> anyhow::Result<()>
+{
+    let type_name = if cmd.object_type.contains("::") {
+        cmd.object_type.clone()
+    } else {
+        format!("default::{}", cmd.object_type)
+    };
+    let format = match cmd.format {
+        Some(format) => format,
+        None => detect_format(&cmd.file)?,
+    };
+    let info = get_type(cli, &type_name).await?;
+    let mut lookups = Vec::with_capacity(cmd.links.len());
+    for mapping in &cmd.links {
+        let Some(link) = info.links.iter().find(|l| l.name == mapping.link)
+        else {
+            return Err(anyhow::anyhow!("{} has no link {:?}",
+                                       type_name, mapping.link))
+                .hint("Run `edgedb describe object` to see its links")?;
+        };
+        let key = get_key(cli, &link.target, &mapping.property).await?;
+        lookups.push(Lookup {
+            link: link.name.clone(),
+            target: link.target.clone(),
+            property: mapping.property.clone(),
+            property_type: key.target,
+        });
+    }
+    let mapping = Mapping::new(cmd, &info, &type_name)?;
+    let file = fs::File::open(&cmd.file).await
+        .with_context(|| format!("cannot open {}", cmd.file.display()))?;
+    let rejected_file = match &cmd.rejected {
+        Some(path) => Some(io::BufWriter::new(
+            fs::File::create(path).await
+            .with_context(|| format!("cannot create {}", path.display()))?
+        )),
+        None => None,
+    };
+    let bar = ProgressBar::new(file.metadata().await?.len());
+    bar.set_style(
+        ProgressStyle::default_bar()
+        .template("{msg} [{bar:30}] {bytes:>7.dim}/{total_bytes:7} \
+                   | ETA: {eta}")
+        .expect("template is ok")
+        .progress_chars("=> "));
+    bar.set_message(type_name.clone());
+    let mut loader = Loader {
+        cli,
+        cmd,
+        type_name,
+        info,
+        mapping,
+        lookups,
+        format,
+        batch: Vec::new(),
+        pointers: Vec::new(),
+        rejected_file,
+        bar,
+        inserted: 0,
+        duplicates: 0,
+        rejected: 0,
+    };
+    let file = file.into_std().await;
+    let (tx, rx) = mpsc::channel(READ_AHEAD);
+    // parsers are blocking, the thread stops when the receiver is dropped
+    let reader = unblock(move || match format {
+        LoadFormat::Csv => read_csv(file, tx),
+        LoadFormat::Jsonl => read_jsonl(file, tx),
+    });
+    let result = match loader.read(rx).await {
+        Ok(()) => match reader.await {
+            Ok(Ok(())) => loader.flush().await,
+            Ok(Err(e)) => Err(e),
+            Err(e) => Err(e.into()),
+        },
+        Err(e) => Err(e),
+    };
+    loader.bar.finish_and_clear();
+    if let Some(out) = &mut loader.rejected_file {
+        out.flush().await?;
+    }
+    loader.summary();
+    result
+}
+
+fn detect_format(path: &Path) -> anyhow::Result<LoadFormat> {
+    let format = match path.extension().and_then(|e| e.to_str()) {
+        Some("csv") => LoadFormat::Csv,
+        Some("jsonl" | "ndjson") => LoadFormat::Jsonl,
+        _ => {
+            return Err(anyhow::anyhow!("cannot detect format of {}",
+                                       path.display()))
+                .hint("Use `--format csv` or `--format jsonl`")?;
+        }
+    };
+    Ok(format)
+}
+
+async fn get_type(cli: &mut Connection, name: &str)
+    -> anyhow::Result<TypeInfo>
+{
+    let data = cli.query_single::<String, _>(r###"
+        WITH MODULE schema
+        SELECT to_str(<json>(
+            SELECT ObjectType {
+                properties: {
+                    name,
+                    target := .target.name,
+                    multi := .cardinality = Cardinality.Many,
+                }
+                FILTER NOT EXISTS .expr AND .name != 'id',
+                links: {
+                    name,
+                    target := .target.name,
+                }
+                FILTER NOT EXISTS .expr AND .name != '__type__',
+            }
+            FILTER .name = <str>$0
+        ))
+    "###, &(name,)).await?;
+    let Some(data) = data else {
+        return Err(anyhow::anyhow!("object type {:?} does not exist", name))
+            .hint("Run `edgedb list types` to see existing types")?;
+    };
+    Ok(serde_json::from_str(&data).context("cannot decode type")?)
+}
+
+async fn get_key(cli: &mut Connection, type_name: &str, property: &str)
+    -> anyhow::Result<KeyInfo>
+{
+    let data = cli.query_single::<String, _>(r###"
+        WITH MODULE schema
+        SELECT to_str(<json>(
+            SELECT Property {
+                target := .target.name,
+                exclusive := EXISTS (
+                    SELECT .constraints FILTER .name = 'std::exclusive'
+                ),
+            }
+            FILTER .source.name = <str>$0 AND .name = <str>$1
+            LIMIT 1
+        ))
+    "###, &(type_name, property)).await?;
+    let Some(data) = data else {
+        anyhow::bail!("{} has no property {:?}", type_name, property);
+    };
+    let key: KeyInfo = serde_json::from_str(&data)
+        .context("cannot decode property")?;
+    if !key.exclusive {
+        return Err(anyhow::anyhow!("property {:?} of {} is not exclusive",
+                                   property, type_name))
+            .hint("Links can only be resolved by a property with \
+                   an exclusive constraint")?;
+    }
+    Ok(key)
+}
+
+fn read_csv(file: std::fs::File, tx: mpsc::Sender<Input>)
+    -> anyhow::Result<()>
+{
+    let mut reader = csv::ReaderBuilder::new()
+        .flexible(true)
+        .from_reader(file);
+    let headers = reader.headers()
+        .context("cannot read CSV header")?
+        .clone();
+    let columns = headers.iter().map(|c| c.to_string()).collect();
+    if tx.blocking_send(Input::Header(columns)).is_err() {
+        return Ok(());
+    }
+    let mut record = csv::StringRecord::new();
+    loop {
+        let input = match reader.read_record(&mut record) {
+            Ok(false) => break,
+            Ok(true) => {
+                let line = record.position().map(|p| p.line()).unwrap_or(0);
+                let position = reader.position().byte();
+                if record.len() != headers.len() {
+                    let error = format!("expected {} fields, found {}",
+                                        headers.len(), record.len());
+                    Input::Invalid { line, error, position }
+                } else {
+                    let data = headers.iter().zip(record.iter())
+                        .map(|(column, value)| {
+                            // empty cells are loaded as empty sets
+                            let value = if value.is_empty() {
+                                Value::Null
+                            } else {
+                                Value::String(value.into())
+                            };
+                            (column.to_string(), value)
+                        })
+                        .collect();
+                    Input::Row { line, data, position }
+                }
+            }
+            Err(e) if matches!(e.kind(), csv::ErrorKind::Io(_)) => {
+                return Err(e).context("cannot read CSV row");
+            }
+            Err(e) => Input::Invalid {
+                line: e.position().map(|p| p.line()).unwrap_or(0),
+                error: e.to_string(),
+                position: reader.position().byte(),
+            },
+        };
+        if tx.blocking_send(input).is_err() {
+            break;
+        }
+    }
+    Ok(())
+}
+
+fn read_jsonl(file: std::fs::File, tx: mpsc::Sender<Input>)
+    -> anyhow::Result<()>
+{
+    let mut reader = BufReader::new(file);
+    let mut text = String::new();
+    let mut line = 0;
+    let mut position = 0;
+    loop {
+        text.clear();
+        let len = reader.read_line(&mut text)?;
+        if len == 0 {
+            break;
+        }
+        line += 1;
+        position += len as u64;
+        if text.trim().is_empty() {
+            continue;
+        }
+        let input = match serde_json::from_str(&text) {
+            Ok(data) => Input::Row { line, data, position },
+            Err(e) => Input::Invalid {
+                line,
+                error: format!("invalid JSON object: {}", e),
+                position,
+            },
+        };
+        if tx.blocking_send(input).is_err() {
+            break;
+        }
+    }
+    Ok(())
+}
+
+impl Mapping {
+    fn new(cmd: &Load, info: &TypeInfo, type_name: &str)
+        -> anyhow::Result<Mapping>
+    {
+        let mut properties = BTreeMap::new();
+        for item in &cmd.mapping {
+            if !info.properties.iter().any(|p| p.name == item.property) {
+                return Err(anyhow::anyhow!("{} has no property {:?}",
+                                           type_name, item.property))
+                    .hint("Run `edgedb describe object` to see \
+                           its properties")?;
+            }
+            properties.insert(item.column.clone(), item.property.clone());
+        }
+        let links = cmd.links.iter()
+            .map(|item| (item.column.clone(), item.link.clone()))
+            .collect();
+        Ok(Mapping {
+            properties,
+            links,
+            infer: cmd.mapping.is_empty(),
+        })
+    }
+    /// Returns the pointer the column is loaded into and whether it's a link
+    fn pointer<'a>(&'a self, column: &'a str, info: &TypeInfo)
+        -> Result<Option<(&'a str, bool)>, String>
+    {
+        if let Some(link) = self.links.get(column) {
+            Ok(Some((&link[..], true)))
+        } else if let Some(property) = self.properties.get(column) {
+            Ok(Some((&property[..], false)))
+        } else if !self.infer {
+            Ok(None)
+        } else if info.properties.iter().any(|p| p.name == column) {
+            Ok(Some((column, false)))
+        } else {
+            Err(format!("column {:?} does not match any property", column))
+        }
+    }
+    fn item(&self, data: &Map<String, Value>, info: &TypeInfo)
+        -> Result<Map<String, Value>, String>
+    {
+        let mut item = Map::new();
+        for (column, value) in data {
+            match self.pointer(column, info)? {
+                // looked up by the string representation of the value
+                Some((link, true)) => {
+                    let key = match value {
+                        Value::Null | Value::String(_) => value.clone(),
+                        _ => Value::String(value.to_string()),
+                    };
+                    item.insert(link.into(), key);
+                }
+                Some((property, false)) => {
+                    item.insert(property.into(), value.clone());
+                }
+                None => {}
+            }
+        }
+        Ok(item)
+    }
+}
+
+impl Loader<'_> {
+    async fn read(&mut self, mut rx: mpsc::Receiver<Input>)
+        -> anyhow::Result<()>
+    {
+        while let Some(input) = rx.recv().await {
+            match input {
+                Input::Header(columns) => {
+                    // fail early instead of rejecting every row
+                    for column in &columns {
+                        self.mapping.pointer(column, &self.info)
+                            .map_err(|e| anyhow::anyhow!(e))
+                            .hint("Use `--map COLUMN=PROPERTY` to choose \
+                                   the loaded columns")?;
+                    }
+                }
+                Input::Row { line, data, position } => {
+                    self.push(line, data).await?;
+                    self.bar.set_position(position);
+                }
+                Input::Invalid { line, error, position } => {
+                    let row = Row { line, data: Map::new(), item: Map::new() };
+                    self.reject(&row, &error).await?;
+                    self.bar.set_position(position);
+                }
+            }
+        }
+        Ok(())
+    }
+    async fn push(&mut self, line: u64, data: Map<String, Value>)
+        -> anyhow::Result<()>
+    {
+        let item = match self.mapping.item(&data, &self.info) {
+            Ok(item) => item,
+            Err(e) => {
+                let row = Row { line, data, item: Map::new() };
+                return self.reject(&row, &e).await;
+            }
+        };
+        let mut pointers = item.keys().cloned().collect::<Vec<_>>();
+        pointers.sort();
+        if pointers != self.pointers {
+            self.flush().await?;
+            self.pointers = pointers;
+        }
+        self.batch.push(Row { line, data, item });
+        if self.batch.len() as u64 >= self.cmd.batch_size {
+            self.flush().await?;
+        }
+        Ok(())
+    }
+    async fn flush(&mut self) -> anyhow::Result<()> {
+        let batch = mem::take(&mut self.batch);
+        let batch = self.resolve_links(batch).await?;
+        if batch.is_empty() {
+            return Ok(());
+        }
+        let query = self.insert_query();
+        match self.insert(&query, &batch).await {
+            Ok(inserted) => self.count(batch.len(), inserted),
+            Err(e) if e.is::<ClientError>() => return Err(e.into()),
+            Err(_) => {
+                // find the rows failing the statement by inserting them
+                // one by one
+                for row in &batch {
+                    match self.insert(&query, slice::from_ref(row)).await {
+                        Ok(inserted) => self.count(1, inserted),
+                        Err(e) if e.is::<ClientError>() => {
+                            return Err(e.into());
+                        }
+                        Err(e) => self.reject(row, &e.to_string()).await?,
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+    /// Rejects rows referring to objects that don't exist
+    async fn resolve_links(&mut self, mut batch: Vec<Row>)
+        -> anyhow::Result<Vec<Row>>
+    {
+        for idx in 0..self.lookups.len() {
+            let lookup = &self.lookups[idx];
+            let keys = batch.iter()
+                .filter_map(|row| row.item.get(&lookup.link))
+                .filter(|key| !key.is_null())
+                .collect::<Vec<_>>();
+            if keys.is_empty() {
+                continue;
+            }
+            let query = format!(r###"
+                SELECT <str>(
+                    SELECT {target}
+                    FILTER <str>.{property} IN
+                        <str>json_array_unpack(to_json(<str>$0))
+                ).{property}
+            "###,
+                target=type_expr(&lookup.target),
+                property=quote_name(&lookup.property));
+            let keys = serde_json::to_string(&keys)?;
+            let found = self.cli.query::<String, _>(&query, &(keys,)).await?
+                .into_iter().collect::<HashSet<_>>();
+            let (ok, missing): (Vec<_>, Vec<_>) = batch.into_iter()
+                .partition(|row| {
+                    match row.item.get(&lookup.link).and_then(|k| k.as_str()) {
+                        Some(key) => found.contains(key),
+                        None => true,
+                    }
+                });
+            batch = ok;
+            for row in &missing {
+                let lookup = &self.lookups[idx];
+                let error = format!("no {} with {} = {}",
+                    lookup.target, lookup.property, row.item[&lookup.link]);
+                self.reject(row, &error).await?;
+            }
+        }
+        Ok(batch)
+    }
+    fn insert_query(&self) -> String {
+        let mut elements = Vec::with_capacity(self.pointers.len());
+        for name in &self.pointers {
+            let value = format!("json_get(item, {})", quote_string(name));
+            let expr = if let Some(lookup) = self.lookups.iter()
+                .find(|l| &l.link == name)
+            {
+                format!("(SELECT {} FILTER .{} = <{}><str>{})",
+                        type_expr(&lookup.target),
+                        quote_name(&lookup.property),
+                        type_expr(&lookup.property_type),
+                        value)
+            } else {
+                let prop = self.info.properties.iter()
+                    .find(|p| &p.name == name)
+                    .expect("mapped to a property");
+                let target = type_expr(&prop.target);
+                match self.format {
+                    // all values of CSV are strings
+                    LoadFormat::Csv => format!("<{}><str>{}", target, value),
+                    LoadFormat::Jsonl if prop.multi => {
+                        format!("array_unpack(<array<{}>>{})", target, value)
+                    }
+                    LoadFormat::Jsonl => format!("<{}>{}", target, value),
+                }
+            };
+            elements.push(format!("{} := {}", quote_name(name), expr));
+        }
+        format!(r###"
+            SELECT count((
+                FOR item IN json_array_unpack(to_json(<str>$0)) UNION (
+                    INSERT {ty} {{ {elements} }}
+                    UNLESS CONFLICT
+                )
+            ))
+        "###, ty=type_expr(&self.type_name), elements=elements.join(", "))
+    }
+    async fn insert(&mut self, query: &str, rows: &[Row])
+        -> Result<u64, Error>
+    {
+        let items = rows.iter()
+            .map(|row| Value::Object(row.item.clone()))
+            .collect();
+        let data = Value::Array(items).to_string();
+        let inserted = self.cli.query_required_single::<i64, _>(
+            query, &(data,),
+        ).await?;
+        Ok(inserted as u64)
+    }
+    fn count(&mut self, rows: usize, inserted: u64) {
+        self.inserted += inserted;
+        self.duplicates += rows as u64 - inserted;
+    }
+    async fn reject(&mut self, row: &Row, error: &str)
+        -> anyhow::Result<()>
+    {
+        self.rejected += 1;
+        if let Some(out) = &mut self.rejected_file {
+            let record = RejectedRow { line: row.line, error, row: &row.data };
+            let mut data = serde_json::to_vec(&record)?;
+            data.push(b'\n');
+            out.write_all(&data).await?;
+        }
+        if self.cmd.on_error == OnError::Abort {
+            return Err(anyhow::anyhow!("line {}: {}", row.line, error))
+                .hint("Use `--on-error skip` to load the other rows")?;
+        }
+        if self.rejected_file.is_none() {
+            self.bar.suspend(|| {
+                print::warn(format!("Rejected line {}: {}", row.line, error));
+            });
+        }
+        Ok(())
+    }
+    fn summary(&self) {
+        print::success_msg("Loaded",
+            format!("{} objects into {}", self.inserted, self.type_name));
+        if self.duplicates > 0 {
+            print::warn(format!("{} rows already exist and were skipped",
+                                self.duplicates));
+        }
+        if self.rejected > 0 {
+            match &self.cmd.rejected {
+                Some(path) => {
+                    print::warn(format!("{} rows rejected, see {}",
+                                        self.rejected, path.display()));
+                }
+                None => print::warn(format!("{} rows rejected",
+                                            self.rejected)),
+            }
+        }
+    }
+}
@@ -1,23 +1,38 @@
 mod exit;
+mod config_auth;
+mod config_file;
+mod config_schema;
 mod configure;
 mod database;
 mod describe;
 mod describe_schema;
 mod dump;
+mod dump_file;
 mod execute;
+mod export;
 mod filter;
 mod helpers;
+mod import;
 mod list;
 mod list_aliases;
 mod list_casts;
+mod list_constraints;
 mod list_databases;
+mod list_extensions;
+mod list_functions;
+mod list_globals;
 mod list_indexes;
 mod list_modules;
 mod list_object_types;
+mod list_operators;
+mod list_policies;
 mod list_roles;
 mod list_scalar_types;
+mod load;
 mod psql;
 mod restore;
+mod role;
+mod signature;
 mod info;
 pub mod backslash;
 pub mod cli;
@@ -27,16 +42,26 @@ mod ui;
 
 pub use self::configure::configure;
 pub use self::dump::{dump, dump_all};
+pub use self::dump_file::dump_file;
 pub use self::describe::describe;
+pub use self::export::export;
+pub use self::import::import;
 pub use self::describe_schema::describe_schema;
 pub use self::list_aliases::list_aliases;
 pub use self::list_casts::list_casts;
+pub use self::list_constraints::list_constraints;
 pub use self::list_databases::list_databases;
-pub use self::list_indexes::list_indexes;
+pub use self::list_extensions::list_extensions;
+pub use self::list_functions::list_functions;
+pub use self::list_globals::list_globals;
+pub use self::list_indexes::{list_indexes, get_indexes};
 pub use self::list_modules::list_modules;
 pub use self::list_object_types::list_object_types;
+pub use self::list_operators::list_operators;
+pub use self::list_policies::{list_policies, list_triggers, list_rewrites};
 pub use self::list_roles::list_roles;
 pub use self::list_scalar_types::list_scalar_types;
+pub use self::load::load;
 pub use self::options::Options;
 pub use self::restore::{restore, restore_all};
 pub use self::psql::psql;
@@ -15,6 +15,12 @@ pub enum Common {
     Dump(Dump),
     /// Restore database from backup file
     Restore(Restore),
+    /// Export data as JSON Lines, one file per object type
+    Export(Export),
+    /// Import data written by `edgedb export`
+    Import(Import),
+    /// Insert rows of a CSV or JSON Lines file as objects of a type
+    Load(Load),
     /// Modify database configuration
     Configure(Configure),
 
@@ -25,6 +31,8 @@ pub enum Common {
 
     /// Database commands
     Database(Database),
+    /// Role (user) management commands
+    Role(Role),
     /// Describe database schema or object
     Describe(Describe),
 
@@ -88,6 +96,27 @@ pub struct Analyze {
     /// Show detailed output of analyze command
     #[arg(long)]
     pub expand: bool,
+
+    /// Compare the analysis with a previous run saved by
+    /// `--debug-output-file`
+    #[arg(long, value_hint=ValueHint::FilePath,
+          conflicts_with="debug_output_file")]
+    pub compare: Option<PathBuf>,
+
+    /// Suggest indexes for sequential scans over large relations
+    #[arg(long, conflicts_with="debug_output_file")]
+    pub advise: bool,
+
+    /// Analyze every query in the file. Queries may be annotated by a
+    /// `# max-cost: <number>` comment to fail if the cost is exceeded
+    #[arg(long, value_hint=ValueHint::FilePath, conflicts_with_all=[
+        "query", "read_json", "debug_output_file", "compare", "advise",
+    ])]
+    pub file: Option<PathBuf>,
+
+    /// Print a report for the queries from `--file` instead of plans
+    #[arg(long, value_enum, requires="file")]
+    pub report: Option<ReportFormat>,
 }
 
 #[derive(clap::Subcommand, Clone, Debug)]
@@ -96,16 +125,32 @@ pub enum ListCmd {
     Aliases(ListAliases),
     /// Display list of casts defined in the schema
     Casts(ListCasts),
+    /// Display list of abstract constraints defined in the schema
+    Constraints(ListTypes),
     /// Display list of databases for an EdgeDB instance
-    Databases,
+    Databases(ListDatabases),
+    /// Display list of installed extensions and their versions
+    Extensions(ListModules),
+    /// Display list of functions and their signatures
+    Functions(ListTypes),
+    /// Display list of globals, in the REPL also shows their current values
+    Globals(ListModules),
     /// Display list of indexes defined in the schema
     Indexes(ListIndexes),
     /// Display list of modules defined in the schema
     Modules(ListModules),
+    /// Display list of operators and their signatures
+    Operators(ListTypes),
+    /// Display list of access policies grouped by object type
+    Policies(ListModules),
+    /// Display list of mutation rewrites grouped by object type
+    Rewrites(ListModules),
     /// Display list of roles for an EdgeDB instance
     Roles(ListRoles),
     /// Display list of scalar types defined in the schema
     Scalars(ListTypes),
+    /// Display list of triggers grouped by object type
+    Triggers(ListModules),
     /// Display list of object types defined in the schema
     Types(ListTypes),
 }
@@ -130,6 +175,31 @@ pub enum DatabaseCmd {
     Drop(DropDatabase),
     /// Preserve database while deleting its data
     Wipe(WipeDatabase),
+    /// Rename a database
+    Rename(RenameDatabase),
+    /// Create a copy of a database, including its schema and data
+    Clone(CloneDatabase),
+}
+
+#[derive(clap::Args, Clone, Debug)]
+pub struct Role {
+    #[command(flatten)]
+    pub conn: ConnectionOptions,
+
+    #[command(subcommand)]
+    pub subcommand: RoleCmd,
+}
+
+#[derive(clap::Subcommand, Clone, Debug)]
+pub enum RoleCmd {
+    /// Create a new role
+    Create(CreateRole),
+    /// Change password, name or membership of a role
+    Alter(AlterRole),
+    /// Delete a role
+    Drop(DropRole),
+    /// Show properties of a role
+    Show(ShowRole),
 }
 
 #[derive(clap::Parser, Clone, Debug)]
@@ -279,6 +349,93 @@ pub struct WipeDatabase {
     pub non_interactive: bool,
 }
 
+#[derive(clap::Args, Clone, Debug)]
+pub struct RenameDatabase {
+    pub old_name: String,
+    pub new_name: String,
+}
+
+#[derive(clap::Args, Clone, Debug)]
+pub struct CloneDatabase {
+    /// Database to copy from
+    pub source: String,
+    /// Name of the new database, it must not exist
+    pub target: String,
+}
+
+#[derive(clap::Args, Clone, Debug)]
+pub struct RolePassword {
+    /// Read password from the terminal
+    #[arg(long)]
+    #[arg(conflicts_with_all=&["password_from_stdin", "generate_password"])]
+    pub password: bool,
+    /// Read password from stdin rather than the terminal
+    #[arg(long, conflicts_with="generate_password")]
+    pub password_from_stdin: bool,
+    /// Generate a random password, it's printed unless saved with
+    /// `--save-credentials`
+    #[arg(long)]
+    pub generate_password: bool,
+}
+
+#[derive(clap::Args, Clone, Debug)]
+pub struct CreateRole {
+    pub role_name: String,
+    /// Create a superuser role
+    #[arg(long)]
+    pub superuser: bool,
+    /// Make the role a member of another role (can be specified
+    /// multiple times)
+    #[arg(long, value_name="role")]
+    pub extending: Vec<String>,
+    #[command(flatten)]
+    pub password: RolePassword,
+    /// Save credentials of the new role as an instance with the specified
+    /// name, so it can be used via `-I <name>`. A password is generated
+    /// unless one is provided.
+    #[arg(long, value_name="instance-name")]
+    pub save_credentials: Option<String>,
+    /// Output in JSON format
+    #[arg(long)]
+    pub json: bool,
+}
+
+#[derive(clap::Args, Clone, Debug)]
+pub struct AlterRole {
+    pub role_name: String,
+    /// Rename the role
+    #[arg(long, value_name="new-name")]
+    pub rename: Option<String>,
+    /// Add the role as a member of another role (can be specified
+    /// multiple times)
+    #[arg(long, value_name="role")]
+    pub extending: Vec<String>,
+    /// Remove membership of another role (can be specified multiple times)
+    #[arg(long, value_name="role")]
+    pub drop_extending: Vec<String>,
+    #[command(flatten)]
+    pub password: RolePassword,
+    /// Output in JSON format
+    #[arg(long)]
+    pub json: bool,
+}
+
+#[derive(clap::Args, Clone, Debug)]
+pub struct DropRole {
+    pub role_name: String,
+    /// Drop role without confirming
+    #[arg(long)]
+    pub non_interactive: bool,
+}
+
+#[derive(clap::Args, Clone, Debug)]
+pub struct ShowRole {
+    pub role_name: String,
+    /// Output in JSON format
+    #[arg(long)]
+    pub json: bool,
+}
+
 #[derive(clap::Args, Clone, Debug)]
 pub struct ListAliases {
     pub pattern: Option<String>,
@@ -288,6 +445,9 @@ pub struct ListAliases {
     pub system: bool,
     #[arg(long, short='v')]
     pub verbose: bool,
+    /// Output in JSON format
+    #[arg(long)]
+    pub json: bool,
 }
 
 #[derive(clap::Args, Clone, Debug)]
@@ -295,6 +455,9 @@ pub struct ListCasts {
     pub pattern: Option<String>,
     #[arg(long, short='c')]
     pub case_sensitive: bool,
+    /// Output in JSON format
+    #[arg(long)]
+    pub json: bool,
 }
 
 #[derive(clap::Args, Clone, Debug)]
@@ -306,6 +469,9 @@ pub struct ListIndexes {
     pub system: bool,
     #[arg(long, short='v')]
     pub verbose: bool,
+    /// Output in JSON format
+    #[arg(long)]
+    pub json: bool,
 }
 
 #[derive(clap::Args, Clone, Debug)]
@@ -315,6 +481,9 @@ pub struct ListTypes {
     pub case_sensitive: bool,
     #[arg(long, short='s')]
     pub system: bool,
+    /// Output in JSON format
+    #[arg(long)]
+    pub json: bool,
 }
 
 #[derive(clap::Args, Clone, Debug)]
@@ -322,6 +491,9 @@ pub struct ListRoles {
     pub pattern: Option<String>,
     #[arg(long, short='c')]
     pub case_sensitive: bool,
+    /// Output in JSON format
+    #[arg(long)]
+    pub json: bool,
 }
 
 #[derive(clap::Args, Clone, Debug)]
@@ -329,6 +501,24 @@ pub struct ListModules {
     pub pattern: Option<String>,
     #[arg(long, short='c')]
     pub case_sensitive: bool,
+    /// Output in JSON format
+    #[arg(long)]
+    pub json: bool,
+}
+
+#[derive(clap::Args, Clone, Debug)]
+pub struct ListDatabases {
+    /// Show size, number of objects, last migration and creation time
+    /// of each database
+    #[arg(long, short='v')]
+    pub verbose: bool,
+    /// Output in JSON format (includes the same details as `--verbose`)
+    #[arg(long)]
+    pub json: bool,
+    /// Also count objects in each database, this scans all the data
+    /// so may be slow on large databases
+    #[arg(long)]
+    pub count_objects: bool,
 }
 
 #[derive(clap::Args, Clone, Debug)]
@@ -336,10 +526,16 @@ pub struct DescribeObject {
     pub name: String,
     #[arg(long, short='v')]
     pub verbose: bool,
+    /// Output in JSON format
+    #[arg(long)]
+    pub json: bool,
 }
 
 #[derive(clap::Args, Clone, Debug)]
 pub struct DescribeSchema {
+    /// Output in JSON format
+    #[arg(long)]
+    pub json: bool,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -348,15 +544,40 @@ pub enum DumpFormat {
     Dir,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompressionAlgorithm {
+    Zstd,
+    Gzip,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Compression {
+    pub algorithm: CompressionAlgorithm,
+    /// Algorithm's default level is used if not specified
+    pub level: Option<i32>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(clap::ValueEnum)]
+pub enum ReportFormat {
+    Json,
+    Csv,
+}
+
 #[derive(clap::Args, Clone, Debug)]
+#[command(args_conflicts_with_subcommands=true)]
+#[command(subcommand_negates_reqs=true)]
 pub struct Dump {
     #[command(flatten)]
     pub conn: ConnectionOptions,
 
+    #[command(subcommand)]
+    pub subcommand: Option<DumpCmd>,
+
     /// Path to file write dump to (or directory if `--all` is specified).
     /// Use dash `-` to write to stdout (latter does not work in `--all` mode)
-    #[arg(value_hint=ValueHint::AnyPath)]
-    pub path: PathBuf,
+    #[arg(value_hint=ValueHint::AnyPath, required=true)]
+    pub path: Option<PathBuf>,
     /// Dump all databases and server configuration. `path` is a directory
     /// in this case
     #[arg(long)]
@@ -370,6 +591,109 @@ pub struct Dump {
     /// For `--all`, only `--format=dir` is required.
     #[arg(long, value_enum)]
     pub format: Option<DumpFormat>,
+
+    /// Compress the dump: `zstd` or `gzip`, optionally followed by
+    /// the compression level (e.g. `zstd:19`). Compressed dumps are
+    /// detected automatically on restore
+    #[arg(long, value_name="algorithm[:level]")]
+    pub compress: Option<Compression>,
+
+    /// Encrypt the dump for the specified age recipient (public key
+    /// starting with `age1`). Can be specified multiple times
+    #[arg(long, value_name="recipient")]
+    pub encrypt_to: Vec<String>,
+
+    /// Encrypt the dump with the passphrase stored in the file
+    #[arg(long, value_name="path", conflicts_with="encrypt_to")]
+    #[arg(value_hint=ValueHint::FilePath)]
+    pub passphrase_file: Option<PathBuf>,
+
+    #[command(flatten)]
+    pub databases: AllDatabases,
+}
+
+/// Options of the `--all` mode shared by `dump` and `restore`
+#[derive(clap::Args, Clone, Debug, Default)]
+pub struct AllDatabases {
+    /// Number of databases to process concurrently
+    #[arg(long, value_name="N", requires="all")]
+    #[arg(value_parser=clap::value_parser!(u16).range(1..))]
+    pub jobs: Option<u16>,
+
+    /// Only process databases matching the glob pattern (e.g. `tenant_*`).
+    /// Can be specified multiple times
+    #[arg(long, value_name="pattern", requires="all")]
+    pub include_db: Vec<String>,
+
+    /// Skip databases matching the glob pattern. Can be specified
+    /// multiple times, takes precedence over `--include-db`
+    #[arg(long, value_name="pattern", requires="all")]
+    pub exclude_db: Vec<String>,
+}
+
+#[derive(clap::Args, Clone, Debug, Default)]
+pub struct DecryptOptions {
+    /// File with age identities (`AGE-SECRET-KEY-...` lines) to decrypt
+    /// a dump encrypted with `--encrypt-to`
+    #[arg(long, value_name="path")]
+    #[arg(value_hint=ValueHint::FilePath)]
+    pub identity_file: Option<PathBuf>,
+
+    /// File with the passphrase to decrypt a dump encrypted with
+    /// `--passphrase-file`
+    #[arg(long, value_name="path", conflicts_with="identity_file")]
+    #[arg(value_hint=ValueHint::FilePath)]
+    pub passphrase_file: Option<PathBuf>,
+}
+
+#[derive(clap::Subcommand, Clone, Debug)]
+pub enum DumpCmd {
+    /// Check signature, checksums and completeness of a dump file (or of
+    /// all dump files in a directory) without a server
+    Verify(DumpVerify),
+    /// Show metadata of a dump file
+    Inspect(DumpInspect),
+    /// Print the schema stored in a dump file
+    ExtractSchema(DumpExtractSchema),
+}
+
+#[derive(clap::Args, Clone, Debug)]
+pub struct DumpVerify {
+    /// Dump file or directory created with `--all`
+    #[arg(value_hint=ValueHint::AnyPath)]
+    pub path: PathBuf,
+    #[command(flatten)]
+    pub decrypt: DecryptOptions,
+}
+
+#[derive(clap::Args, Clone, Debug)]
+pub struct DumpInspect {
+    #[arg(value_hint=ValueHint::AnyPath)]
+    pub path: PathBuf,
+    #[command(flatten)]
+    pub decrypt: DecryptOptions,
+    /// Output in JSON format
+    #[arg(long)]
+    pub json: bool,
+}
+
+#[derive(clap::Args, Clone, Debug)]
+pub struct DumpExtractSchema {
+    #[arg(value_hint=ValueHint::FilePath)]
+    pub path: PathBuf,
+    #[command(flatten)]
+    pub decrypt: DecryptOptions,
+    /// Output the schema as DDL commands (default). Works without a server
+    #[arg(long, conflicts_with="sdl")]
+    pub ddl: bool,
+    /// Output the schema as SDL. Requires a server: the schema is applied
+    /// to a temporary database which is dropped afterwards
+    #[arg(long)]
+    pub sdl: bool,
+    /// Write the schema to `schema.edgeql` (or `default.esdl` for `--sdl`)
+    /// in the directory instead of stdout
+    #[arg(long, value_name="dir", value_hint=ValueHint::DirPath)]
+    pub out: Option<PathBuf>,
 }
 
 #[derive(clap::Args, Clone, Debug)]
@@ -394,6 +718,164 @@ pub struct Restore {
     /// Verbose output
     #[arg(long, short='v')]
     pub verbose: bool,
+
+    #[command(flatten)]
+    pub decrypt: DecryptOptions,
+
+    #[command(flatten)]
+    pub databases: AllDatabases,
+
+    /// Do not apply `init.edgeql` (server configuration and roles)
+    #[arg(long, requires="all", conflicts_with="only_init")]
+    pub skip_init: bool,
+
+    /// Only apply `init.edgeql`, do not restore any databases
+    #[arg(long, requires="all")]
+    pub only_init: bool,
+
+    /// Restore into the specified database, it's created if it doesn't
+    /// exist yet
+    #[arg(long, value_name="name", conflicts_with="all")]
+    pub create_database: Option<String>,
+
+    /// Wipe the target database before restoring if it's not empty
+    #[arg(long)]
+    pub replace: bool,
+
+    /// Do not ask for confirmation of `--replace`
+    #[arg(long, requires="replace")]
+    pub non_interactive: bool,
+
+    /// Restore database `OLD` from the dump directory under the name `NEW`.
+    /// Can be specified multiple times
+    #[arg(long, value_name="OLD=NEW", requires="all")]
+    pub rename: Vec<DatabaseRename>,
+
+    /// How to report progress: `bar` on a terminal, `json` for periodic
+    /// machine-readable records on stdout, or `none`
+    #[arg(long, value_enum, default_value="bar")]
+    pub progress: ProgressFormat,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(clap::ValueEnum)]
+pub enum ProgressFormat {
+    Bar,
+    Json,
+    None,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DatabaseRename {
+    pub old_name: String,
+    pub new_name: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(clap::ValueEnum)]
+pub enum ExportFormat {
+    Jsonl,
+}
+
+#[derive(clap::Args, Clone, Debug)]
+pub struct Export {
+    #[command(flatten)]
+    pub conn: ConnectionOptions,
+
+    /// Output format, only `jsonl` (one JSON object per line) is supported
+    #[arg(long, value_enum, default_value="jsonl")]
+    pub format: ExportFormat,
+
+    /// Directory to write `manifest.json` and data files to
+    #[arg(long, value_hint=ValueHint::DirPath)]
+    pub dir: PathBuf,
+
+    /// Continue an interrupted export in the same directory
+    #[arg(long)]
+    pub resume: bool,
+}
+
+#[derive(clap::Args, Clone, Debug)]
+pub struct Import {
+    #[command(flatten)]
+    pub conn: ConnectionOptions,
+
+    /// Directory written by `edgedb export`
+    #[arg(long, value_hint=ValueHint::DirPath)]
+    pub dir: PathBuf,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(clap::ValueEnum)]
+pub enum LoadFormat {
+    Csv,
+    Jsonl,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(clap::ValueEnum)]
+pub enum OnError {
+    Skip,
+    Abort,
+}
+
+#[derive(clap::Args, Clone, Debug)]
+pub struct Load {
+    #[command(flatten)]
+    pub conn: ConnectionOptions,
+
+    /// Object type to insert (e.g. `default::Movie`), the module defaults
+    /// to `default`
+    #[arg(long="type", value_name="name")]
+    pub object_type: String,
+
+    /// CSV file with a header row, or JSON Lines file with an object
+    /// per line
+    #[arg(long, value_hint=ValueHint::FilePath)]
+    pub file: PathBuf,
+
+    /// Format of the file, detected by the extension if not specified
+    #[arg(long, value_enum)]
+    pub format: Option<LoadFormat>,
+
+    /// Load column `COLUMN` into property `PROPERTY`. Can be specified
+    /// multiple times. If omitted, each column is loaded into the property
+    /// of the same name
+    #[arg(long="map", value_name="COLUMN=PROPERTY")]
+    pub mapping: Vec<ColumnMapping>,
+
+    /// Set link `LINK` to the object whose exclusive property `PROPERTY`
+    /// is equal to the value of column `COLUMN`. Can be specified multiple
+    /// times
+    #[arg(long="link", value_name="COLUMN=LINK.PROPERTY")]
+    pub links: Vec<LinkMapping>,
+
+    /// Number of rows inserted by a single statement
+    #[arg(long, value_name="N", default_value="500")]
+    #[arg(value_parser=clap::value_parser!(u64).range(1..))]
+    pub batch_size: u64,
+
+    /// What to do with a row that cannot be inserted: `skip` it and report
+    /// it at the end, or `abort` loading
+    #[arg(long, value_enum, default_value="abort")]
+    pub on_error: OnError,
+
+    /// Write rejected rows along with the errors to the file (JSON Lines)
+    #[arg(long, value_name="path", value_hint=ValueHint::FilePath)]
+    pub rejected: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ColumnMapping {
+    pub column: String,
+    pub property: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LinkMapping {
+    pub column: String,
+    pub link: String,
+    pub property: String,
 }
 
 #[derive(clap::Args, Clone, Debug)]
@@ -401,18 +883,118 @@ pub struct Configure {
     #[command(flatten)]
     pub conn: ConnectionOptions,
 
+    /// Which configuration to change: the whole instance, current database
+    /// or the current session (the latter only makes sense in the REPL)
+    #[arg(long, value_enum, default_value="instance", global=true)]
+    pub scope: ConfigScope,
+
     #[command(subcommand)]
     pub command: ConfigureCommand,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(clap::ValueEnum)]
+pub enum ConfigScope {
+    Instance,
+    Database,
+    Session,
+}
+
 #[derive(clap::Subcommand, Clone, Debug)]
 pub enum ConfigureCommand {
+    /// Apply configuration from a TOML file, only changed values are
+    /// configured
+    ///
+    /// Apply is not atomic. Database settings are changed in a transaction,
+    /// but instance configuration can't be, so if a statement fails,
+    /// instance changes already made are reverted one by one.
+    Apply(ConfigureApply),
+    /// List, remove or edit client authentication rules
+    Auth(ConfigureAuth),
+    /// Print non-default instance and database configuration as TOML
+    Export,
     /// Insert another configuration entry to the list setting
     Insert(ConfigureInsert),
     /// Reset configuration entry (empty the list for list settings)
     Reset(ConfigureReset),
     /// Set scalar configuration value
     Set(ConfigureSet),
+    /// Show effective configuration values and where they come from
+    Show(ConfigureShow),
+}
+
+#[derive(clap::Args, Clone, Debug)]
+pub struct ConfigureApply {
+    /// Configuration file as written by `configure export`
+    pub file: PathBuf,
+    /// Print statements that would be executed, don't change anything
+    #[arg(long)]
+    pub dry_run: bool,
+}
+
+#[derive(clap::Args, Clone, Debug)]
+pub struct ConfigureAuth {
+    #[command(subcommand)]
+    pub subcommand: AuthCmd,
+}
+
+#[derive(clap::Subcommand, Clone, Debug)]
+pub enum AuthCmd {
+    /// Show authentication rules ordered by priority
+    List(AuthList),
+    /// Remove a single authentication rule
+    Remove(AuthRemove),
+    /// Change a single authentication rule, options that are not specified
+    /// are kept intact
+    Edit(AuthEdit),
+}
+
+#[derive(clap::Args, Clone, Debug)]
+pub struct AuthList {
+    /// Output in JSON format
+    #[arg(long)]
+    pub json: bool,
+}
+
+#[derive(clap::Args, Clone, Debug)]
+pub struct AuthRemove {
+    /// Priority of the rule to remove
+    #[arg(long)]
+    pub priority: i64,
+    /// Remove the rule without confirming
+    #[arg(long)]
+    pub non_interactive: bool,
+}
+
+#[derive(clap::Args, Clone, Debug)]
+pub struct AuthEdit {
+    /// Priority of the rule to change
+    #[arg(long)]
+    pub priority: i64,
+    /// Change priority of the rule
+    #[arg(long)]
+    pub new_priority: Option<i64>,
+    /// Replace role(s) this rule applies to
+    #[arg(long="user")]
+    pub users: Vec<String>,
+    /// Change authentication method (e.g. `SCRAM`, `JWT`, `mTLS`)
+    #[arg(long)]
+    pub method: Option<String>,
+    /// Replace transports the method is restricted to (e.g. `TCP`, `HTTP`)
+    #[arg(long="transport")]
+    pub transports: Vec<String>,
+    /// Change comment of the rule (empty string removes the comment)
+    #[arg(long)]
+    pub comment: Option<String>,
+}
+
+#[derive(clap::Args, Clone, Debug)]
+pub struct ConfigureShow {
+    /// Name of the setting to show (all settings are shown by default)
+    pub name: Option<String>,
+    /// Output in JSON format
+    #[arg(long)]
+    pub json: bool,
 }
 
 #[derive(clap::Args, Clone, Debug)]
@@ -521,6 +1103,11 @@ pub enum ValueParameter {
 
     /// Allow setting user-specified object identifiers.
     AllowUserSpecifiedId(ConfigBool),
+
+    /// Any other setting (including extension settings), type of the value
+    /// is discovered from the schema
+    #[command(external_subcommand)]
+    Other(Vec<String>),
 }
 
 #[derive(clap::Subcommand, Clone, Debug)]
@@ -557,6 +1144,9 @@ pub enum ConfigParameter {
     ApplyAccessPolicies,
     /// Reset allow_user_specified_id parameter to `false`
     AllowUserSpecifiedId,
+    /// Any other setting, validated against the schema
+    #[command(external_subcommand)]
+    Other(Vec<String>),
 }
 
 #[derive(clap::Args, Clone, Debug)]
@@ -591,18 +1181,33 @@ pub struct AuthParameter {
     #[arg(long="user")]
     pub users: Vec<String>,
 
-    /// The name of the authentication method type. Valid values are: Trust
-    /// for no authentication and SCRAM for SCRAM-SHA-256 password
-    /// authentication.
+    /// The name of the authentication method type. For example: Trust
+    /// for no authentication, SCRAM for SCRAM-SHA-256 password
+    /// authentication, JWT or mTLS. Supported methods depend on the server.
     #[arg(long)]
     pub method: String,
 
+    /// Restrict the method to specific transport(s), e.g. `TCP`, `HTTP`.
+    #[arg(long="transport")]
+    pub transports: Vec<String>,
+
     /// An optional comment for the authentication rule.
     #[arg(long)]
     pub comment: Option<String>,
 }
 
 
+impl ConfigScope {
+    /// Scope as used in `CONFIGURE <scope> SET ...` statement
+    pub fn as_keyword(&self) -> &'static str {
+        match self {
+            ConfigScope::Instance => "INSTANCE",
+            ConfigScope::Database => "CURRENT DATABASE",
+            ConfigScope::Session => "SESSION",
+        }
+    }
+}
+
 impl SettingBool {
     pub fn unwrap_value(&self) -> bool {
         match self.value.as_deref() {
@@ -615,6 +1220,94 @@ impl SettingBool {
     }
 }
 
+impl std::str::FromStr for Compression {
+    type Err = anyhow::Error;
+    fn from_str(s: &str) -> Result<Compression, anyhow::Error> {
+        let (name, level) = match s.split_once(':') {
+            Some((name, level)) => (name, Some(level)),
+            None => (s, None),
+        };
+        let (algorithm, range) = match name {
+            "zstd" => (CompressionAlgorithm::Zstd, 1..=22),
+            "gzip" => (CompressionAlgorithm::Gzip, 0..=9),
+            _ => anyhow::bail!("unsupported compression {:?}, \
+                                expected `zstd` or `gzip`", name),
+        };
+        let level = match level {
+            Some(level) => {
+                let level = level.parse::<i32>().map_err(|_| {
+                    anyhow::anyhow!("invalid compression level {:?}", level)
+                })?;
+                if !range.contains(&level) {
+                    anyhow::bail!("compression level for {} must be \
+                                   from {} to {}",
+                                  name, range.start(), range.end());
+                }
+                Some(level)
+            }
+            None => None,
+        };
+        Ok(Compression { algorithm, level })
+    }
+}
+
+impl std::str::FromStr for DatabaseRename {
+    type Err = anyhow::Error;
+    fn from_str(s: &str) -> Result<DatabaseRename, anyhow::Error> {
+        match s.split_once('=') {
+            Some((old, new)) if !old.is_empty() && !new.is_empty() => {
+                Ok(DatabaseRename {
+                    old_name: old.into(),
+                    new_name: new.into(),
+                })
+            }
+            _ => anyhow::bail!("invalid rename {:?}, expected `OLD=NEW`", s),
+        }
+    }
+}
+
+impl std::str::FromStr for ColumnMapping {
+    type Err = anyhow::Error;
+    fn from_str(s: &str) -> Result<ColumnMapping, anyhow::Error> {
+        match s.split_once('=') {
+            Some((column, property))
+                if !column.is_empty() && !property.is_empty()
+            => {
+                Ok(ColumnMapping {
+                    column: column.into(),
+                    property: property.into(),
+                })
+            }
+            _ => anyhow::bail!("invalid mapping {:?}, \
+                                expected `COLUMN=PROPERTY`", s),
+        }
+    }
+}
+
+impl std::str::FromStr for LinkMapping {
+    type Err = anyhow::Error;
+    fn from_str(s: &str) -> Result<LinkMapping, anyhow::Error> {
+        let parsed = s.split_once('=').and_then(|(column, pointer)| {
+            let (link, property) = pointer.split_once('.')?;
+            Some((column, link, property))
+        });
+        match parsed {
+            Some((column, link, property))
+                if !column.is_empty() && !link.is_empty()
+                    && !property.is_empty()
+            => {
+                Ok(LinkMapping {
+                    column: column.into(),
+                    link: link.into(),
+                    property: property.into(),
+                })
+            }
+            _ => anyhow::bail!("invalid link {:?}, \
+                                expected `COLUMN=LINK.PROPERTY`", s),
+        }
+    }
+}
+
 impl std::str::FromStr for DumpFormat {
     type Err = anyhow::Error;
     fn from_str(s: &str) -> Result<DumpFormat, anyhow::Error> {
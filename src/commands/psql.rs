@@ -17,34 +17,7 @@ pub async fn psql<'x>(cli: &mut Connection, _options: &Options)
     match cli.get_server_param::<PostgresAddress>() {
         Some(addr) => {
             let mut cmd = Command::new("psql");
-            let path = if cfg!(feature="dev_mode") {
-                use std::iter;
-                use std::path::{Path, PathBuf};
-
-                if let Some(dir) = option_env!("PSQL_DEFAULT_PATH") {
-                    let psql_path = Path::new(dir).join("psql");
-                    if !psql_path.exists() {
-                        eprintln!("WARNING: {} does not exist",
-                                  psql_path.display());
-                    }
-                    let npath = if let Some(path) = env::var_os("PATH") {
-                        env::join_paths(
-                            iter::once(PathBuf::from(dir))
-                            .chain(env::split_paths(&path)))
-                        .unwrap_or_else(|e| {
-                            eprintln!("PSQL_DEFAULT_PATH error: {}", e);
-                            path
-                        })
-                    } else {
-                        dir.into()
-                    };
-                    Some(npath)
-                } else {
-                    env::var_os("PATH")
-                }
-            } else {
-                env::var_os("PATH")
-            };
+            let path = psql_path();
             cmd.arg("-h").arg(&addr.host);
             cmd.arg("-U").arg(&addr.user);
             cmd.arg("-p").arg(addr.port.to_string());
@@ -66,3 +39,36 @@ pub async fn psql<'x>(cli: &mut Connection, _options: &Options)
     }
     Ok(())
 }
+
+/// `PATH` used to run `psql`, in dev mode `PSQL_DEFAULT_PATH` is searched
+/// first
+pub fn psql_path() -> Option<OsString> {
+    if cfg!(feature="dev_mode") {
+        use std::iter;
+        use std::path::{Path, PathBuf};
+
+        if let Some(dir) = option_env!("PSQL_DEFAULT_PATH") {
+            let psql_path = Path::new(dir).join("psql");
+            if !psql_path.exists() {
+                eprintln!("WARNING: {} does not exist",
+                          psql_path.display());
+            }
+            let npath = if let Some(path) = env::var_os("PATH") {
+                env::join_paths(
+                    iter::once(PathBuf::from(dir))
+                    .chain(env::split_paths(&path)))
+                .unwrap_or_else(|e| {
+                    eprintln!("PSQL_DEFAULT_PATH error: {}", e);
+                    path
+                })
+            } else {
+                dir.into()
+            };
+            Some(npath)
+        } else {
+            env::var_os("PATH")
+        }
+    } else {
+        env::var_os("PATH")
+    }
+}
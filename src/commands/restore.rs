@@ -5,29 +5,48 @@ use std::future::Future;
 use std::path::Path;
 use std::pin::Pin;
 use std::str;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::task::{Poll, Context};
-use std::time::{Duration};
+use std::time::{Duration, Instant};
 
 use anyhow::Context as _;
+use async_compression::tokio::bufread::{GzipDecoder, ZstdDecoder};
 use bytes::{Bytes, BytesMut};
 use fn_error_context::context;
+use futures_util::{stream, TryStreamExt};
+use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
 use tokio::fs;
-use tokio::io::{self, AsyncRead, AsyncReadExt};
+use tokio::io::{self, AsyncRead, AsyncReadExt, ReadBuf};
 use tokio_stream::Stream;
+use tokio_util::compat::{FuturesAsyncReadCompatExt, TokioAsyncReadCompatExt};
+use sha1::Digest;
 
 use edgedb_errors::{Error, ErrorKind, UserError};
 use edgeql_parser::helpers::quote_name;
 use edgeql_parser::preparser::{is_empty};
 
-use crate::commands::Options;
+use crate::commands::{ExitCode, Options};
+use crate::commands::database;
+use crate::commands::dump::{read_passphrase, Selection};
 use crate::commands::list_databases;
-use crate::commands::parser::{Restore as RestoreCmd};
+use crate::commands::parser::{Restore as RestoreCmd, DecryptOptions};
+use crate::commands::parser::{ProgressFormat, WipeDatabase};
 use crate::connect::Connection;
+use crate::hint::HintExt;
+use crate::portable::exit_codes;
+use crate::print;
+use crate::question;
 use crate::statement::{read_statement, EndOfFile};
 
-type Input = Box<dyn AsyncRead + Unpin + Send>;
+pub(crate) type Input = Box<dyn AsyncRead + Unpin + Send>;
 
 const MAX_SUPPORTED_DUMP_VER: i64 = 1;
+const DUMP_MAGIC: &[u8] = b"\xFF\xD8\x00\x00\xD8EDGEDB\x00DUMP\x00";
+const ZSTD_MAGIC: &[u8] = b"\x28\xB5\x2F\xFD";
+const GZIP_MAGIC: &[u8] = b"\x1F\x8B";
+const AGE_MAGIC: &[u8] = b"age-encryption.org/v1";
+const JSON_PROGRESS_INTERVAL: Duration = Duration::from_secs(5);
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum PacketType {
@@ -38,11 +57,45 @@ pub enum PacketType {
 pub struct Packets<'a> {
     input: &'a mut Input,
     buf: BytesMut,
+    progress: Progress,
+}
+
+/// Counts bytes read from the dump file, so progress is measured against
+/// the file size even if the dump is compressed or encrypted
+struct Counter {
+    inner: Input,
+    read: Arc<AtomicU64>,
+}
+
+/// Progress of restoring a single database
+struct Progress {
+    format: ProgressFormat,
+    bar: ProgressBar,
+    database: String,
+    read: Arc<AtomicU64>,
+    total: Option<u64>,
+    blocks: u64,
+    started: Instant,
+    reported: Instant,
+}
+
+/// A line of `--progress=json` output
+#[derive(serde::Serialize, Debug)]
+struct ProgressRecord<'a> {
+    database: &'a str,
+    bytes_read: u64,
+    total_bytes: Option<u64>,
+    percent: Option<f64>,
+    blocks: u64,
+    elapsed_secs: f64,
+    bytes_per_sec: f64,
+    eta_secs: Option<f64>,
+    done: bool,
 }
 
 
-async fn read_packet(input: &mut Input, buf: &mut BytesMut,
-                     expected: PacketType)
+pub(crate) async fn read_packet(input: &mut Input, buf: &mut BytesMut,
+                                expected: PacketType)
     -> Result<Option<Bytes>, anyhow::Error>
 {
     const HEADER_LEN: usize = 1+20+4;
@@ -81,15 +134,237 @@ async fn read_packet(input: &mut Input, buf: &mut BytesMut,
                     format!("Error reading block of {} bytes", len))?;
         }
     }
-    Ok(Some(buf.split_to(HEADER_LEN + len).split_off(HEADER_LEN).freeze()))
+    let mut packet = buf.split_to(HEADER_LEN + len);
+    let data = packet.split_off(HEADER_LEN).freeze();
+    if sha1::Sha1::digest(&data)[..] != packet[1..1+20] {
+        return Err(anyhow::anyhow!("Checksum mismatch in {:?} block \
+                                    of {} bytes, the dump is corrupted",
+                                   typ, len));
+    }
+    Ok(Some(data))
+}
+
+/// Wraps the input into a decompressor if the dump is compressed, also
+/// returns the name of the compression algorithm
+pub(crate) async fn decompress(mut input: Input)
+    -> anyhow::Result<(Input, Option<&'static str>)>
+{
+    // a single read may return less than the magic, e.g. from a pipe
+    let mut head = Vec::with_capacity(ZSTD_MAGIC.len());
+    (&mut input).take(ZSTD_MAGIC.len() as u64).read_to_end(&mut head).await
+        .context("Cannot read header")?;
+    let input = io::BufReader::new(io::Cursor::new(head.clone()).chain(input));
+    if head.starts_with(ZSTD_MAGIC) {
+        let mut decoder = ZstdDecoder::new(input);
+        decoder.multiple_members(true);
+        Ok((Box::new(decoder), Some("zstd")))
+    } else if head.starts_with(GZIP_MAGIC) {
+        let mut decoder = GzipDecoder::new(input);
+        decoder.multiple_members(true);
+        Ok((Box::new(decoder), Some("gzip")))
+    } else {
+        Ok((Box::new(input), None))
+    }
+}
+
+/// Wraps the input into a decryptor if the dump is encrypted, also
+/// returns the name of the encryption scheme
+pub(crate) async fn decrypt(mut input: Input, options: &DecryptOptions)
+    -> anyhow::Result<(Input, Option<&'static str>)>
+{
+    // a single read may return less than the magic, e.g. from a pipe
+    let mut head = Vec::with_capacity(AGE_MAGIC.len());
+    (&mut input).take(AGE_MAGIC.len() as u64).read_to_end(&mut head).await
+        .context("Cannot read header")?;
+    let encrypted = head.starts_with(AGE_MAGIC);
+    let input: Input = Box::new(io::Cursor::new(head).chain(input));
+    if !encrypted {
+        return Ok((input, None));
+    }
+    let decryptor = age::Decryptor::new_async(input.compat()).await
+        .context("Cannot read encryption header")?;
+    match decryptor {
+        age::Decryptor::Recipients(decryptor) => {
+            let Some(path) = &options.identity_file else {
+                return Err(anyhow::anyhow!("dump is encrypted to a key"))
+                    .hint("Specify the private key with `--identity-file`")?;
+            };
+            let identities = read_identities(path).await?;
+            let identities = identities.iter()
+                .map(|i| i as &dyn age::Identity);
+            let reader = decryptor.decrypt_async(identities)
+                .context("Cannot decrypt dump")?;
+            Ok((Box::new(reader.compat()), Some("age-x25519")))
+        }
+        age::Decryptor::Passphrase(decryptor) => {
+            let Some(path) = &options.passphrase_file else {
+                return Err(anyhow::anyhow!("dump is encrypted by passphrase"))
+                    .hint("Specify the passphrase with `--passphrase-file`")?;
+            };
+            let passphrase = read_passphrase(path).await?;
+            let reader = decryptor
+                .decrypt_async(&age::secrecy::Secret::new(passphrase), None)
+                .context("Cannot decrypt dump")?;
+            Ok((Box::new(reader.compat()), Some("age-scrypt")))
+        }
+    }
+}
+
+/// Reads private keys in the format of `age-keygen`, comments are skipped
+async fn read_identities(path: &Path)
+    -> anyhow::Result<Vec<age::x25519::Identity>>
+{
+    let data = fs::read_to_string(path).await
+        .with_context(|| format!("cannot read {}", path.display()))?;
+    let identities = data.lines()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| line.parse().map_err(|e| {
+            anyhow::anyhow!("invalid key in {}: {}", path.display(), e)
+        }))
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    if identities.is_empty() {
+        anyhow::bail!("no keys found in {}", path.display());
+    }
+    Ok(identities)
+}
+
+/// Checks the file signature and returns the format version of the dump
+pub(crate) async fn read_version(input: &mut Input) -> anyhow::Result<i64> {
+    let mut buf = [0u8; 17+8];
+    input.read_exact(&mut buf).await
+        .context("Cannot read header")?;
+    if &buf[..17] != DUMP_MAGIC {
+        anyhow::bail!("Incorrect header; file is not an EdgeDB dump");
+    }
+    let version = i64::from_be_bytes(buf[17..].try_into().unwrap());
+    if version == 0 || version > MAX_SUPPORTED_DUMP_VER {
+        anyhow::bail!("Unsupported dump version {}", version);
+    }
+    Ok(version)
 }
 
 impl Packets<'_> {
     async fn next(&mut self) -> Option<Result<Bytes, Error>> {
-        read_packet(self.input, &mut self.buf, PacketType::Block)
+        let packet = read_packet(self.input, &mut self.buf, PacketType::Block)
             .await
             .map_err(UserError::with_source_ref)
-            .transpose()
+            .transpose()?;
+        if packet.is_ok() {
+            self.progress.block();
+        }
+        Some(packet)
+    }
+}
+
+impl AsyncRead for Counter {
+    fn poll_read(mut self: Pin<&mut Self>, cx: &mut Context<'_>,
+                 buf: &mut ReadBuf<'_>)
+        -> Poll<io::Result<()>>
+    {
+        let before = buf.filled().len();
+        let result = Pin::new(&mut self.inner).poll_read(cx, buf);
+        let read = buf.filled().len() - before;
+        self.read.fetch_add(read as u64, Ordering::Relaxed);
+        result
+    }
+}
+
+impl Progress {
+    fn new(format: ProgressFormat, bars: &MultiProgress, database: String,
+           total: Option<u64>, read: Arc<AtomicU64>)
+        -> Progress
+    {
+        let bar = match format {
+            ProgressFormat::Bar => {
+                let bar = match total {
+                    Some(len) => ProgressBar::new(len),
+                    None => ProgressBar::new_spinner(),
+                };
+                let template = if total.is_some() {
+                    "{msg} [{bar:30}] {bytes:>7.dim}/{total_bytes:7} \
+                     {binary_bytes_per_sec:.dim} | ETA: {eta}"
+                } else {
+                    "{spinner} {msg} {bytes:>7.dim} \
+                     {binary_bytes_per_sec:.dim}"
+                };
+                bar.set_style(
+                    ProgressStyle::default_bar()
+                    .template(template)
+                    .expect("template is ok")
+                    .progress_chars("=> "));
+                bar.set_message(database.clone());
+                bars.add(bar)
+            }
+            ProgressFormat::Json | ProgressFormat::None => {
+                ProgressBar::hidden()
+            }
+        };
+        let now = Instant::now();
+        Progress {
+            format,
+            bar,
+            database,
+            read,
+            total,
+            blocks: 0,
+            started: now,
+            reported: now,
+        }
+    }
+    fn block(&mut self) {
+        self.blocks += 1;
+        match self.format {
+            ProgressFormat::Bar => {
+                self.bar.set_position(self.read.load(Ordering::Relaxed));
+            }
+            ProgressFormat::Json => {
+                if self.reported.elapsed() >= JSON_PROGRESS_INTERVAL {
+                    self.reported = Instant::now();
+                    self.report(false);
+                }
+            }
+            ProgressFormat::None => {}
+        }
+    }
+    fn finish(&self) {
+        match self.format {
+            ProgressFormat::Bar => {
+                self.bar.set_position(self.read.load(Ordering::Relaxed));
+                self.bar.abandon_with_message(
+                    format!("{} restored", self.database));
+            }
+            ProgressFormat::Json => self.report(true),
+            ProgressFormat::None => {}
+        }
+    }
+    fn report(&self, done: bool) {
+        let bytes_read = self.read.load(Ordering::Relaxed);
+        let elapsed = self.started.elapsed().as_secs_f64();
+        let bytes_per_sec = if elapsed > 0.0 {
+            bytes_read as f64 / elapsed
+        } else {
+            0.0
+        };
+        let record = ProgressRecord {
+            database: &self.database,
+            bytes_read,
+            total_bytes: self.total,
+            percent: self.total.filter(|&total| total > 0)
+                .map(|total| bytes_read as f64 * 100.0 / total as f64),
+            blocks: self.blocks,
+            elapsed_secs: elapsed,
+            bytes_per_sec,
+            eta_secs: self.total.filter(|_| bytes_per_sec > 0.0)
+                .map(|total| {
+                    total.saturating_sub(bytes_read) as f64 / bytes_per_sec
+                }),
+            done,
+        };
+        match serde_json::to_string(&record) {
+            Ok(line) => println!("{}", line),
+            Err(e) => log::warn!("Cannot serialize progress: {:#}", e),
+        }
     }
 }
 
@@ -125,13 +400,27 @@ pub async fn restore<'x>(cli: &mut Connection, options: &Options,
 {
     if params.all {
         restore_all(cli, options, params).await
+    } else if let Some(database) = &params.create_database {
+        let existing = list_databases::get_databases(cli).await?;
+        if !existing.contains(database) {
+            let stmt = format!("CREATE DATABASE {}", quote_name(database));
+            cli.execute(&stmt, &()).await
+                .with_context(|| format!("error creating database {:?}",
+                                         database))?;
+            print::success_msg("Created database", database);
+        }
+        let mut conn_params = options.conn_params.clone();
+        let mut db_conn = conn_params.database(database)?.connect().await
+            .with_context(|| format!("cannot connect to database {:?}",
+                                     database))?;
+        restore_db(&mut db_conn, options, params, &MultiProgress::new()).await
     } else {
-        restore_db(cli, options, params).await
+        restore_db(cli, options, params, &MultiProgress::new()).await
     }
 }
 
-async fn restore_db<'x>(cli: &mut Connection, _options: &Options,
-    params: &RestoreCmd)
+async fn restore_db<'x>(cli: &mut Connection, options: &Options,
+    params: &RestoreCmd, bars: &MultiProgress)
     -> Result<(), anyhow::Error>
 {
     use PacketType::*;
@@ -140,44 +429,60 @@ async fn restore_db<'x>(cli: &mut Connection, _options: &Options,
         all: _,
         verbose: _,
         conn: _,
+        decrypt: ref decrypt_options,
+        databases: _,
+        skip_init: _,
+        only_init: _,
+        create_database: _,
+        replace,
+        non_interactive,
+        rename: _,
+        progress,
     } = *params;
     if is_non_empty_db(cli).await? {
-        return Err(anyhow::anyhow!("\
-            cannot restore: the database is not empty"));
+        if !replace {
+            return Err(anyhow::anyhow!("\
+                cannot restore: the database is not empty"))
+                .hint("Use `--replace` to wipe the database first")?;
+        }
+        database::wipe(cli, &WipeDatabase { non_interactive }, options).await?;
     }
 
     let file_ctx = &|| format!("Failed to read dump {}", filename.display());
-    let mut input = if filename.to_str() == Some("-") {
-        Box::new(io::stdin()) as Input
+    let (input, total) = if filename.to_str() == Some("-") {
+        (Box::new(io::stdin()) as Input, None)
     } else {
         let file = fs::File::open(filename).await.with_context(file_ctx)?;
         let file_size = file.metadata().await?.len();
-        eprintln!("\nRestoring database from file `{}`. Total size: {:.02} MB", filename.display(), file_size as f64 / 1048576.0);
-        Box::new(file)
-        as Input
+        if progress == ProgressFormat::Bar {
+            bars.suspend(|| {
+                eprintln!("\nRestoring database from file `{}`. \
+                           Total size: {:.02} MB",
+                          filename.display(), file_size as f64 / 1048576.0)
+            });
+        }
+        (Box::new(file) as Input, Some(file_size))
     };
-    let mut buf = [0u8; 17+8];
-    input.read_exact(&mut buf).await
-        .context("Cannot read header")
+    let read = Arc::new(AtomicU64::new(0));
+    let input = Box::new(Counter { inner: input, read: read.clone() });
+    let (input, _) = decrypt(input, decrypt_options).await
         .with_context(file_ctx)?;
-    if &buf[..17] != b"\xFF\xD8\x00\x00\xD8EDGEDB\x00DUMP\x00" {
-        Err(anyhow::anyhow!("Incorrect header; file is not an EdgeDB dump"))
-        .with_context(file_ctx)?
-    }
-    let version = i64::from_be_bytes(buf[17..].try_into().unwrap());
-    if version == 0 || version > MAX_SUPPORTED_DUMP_VER {
-        Err(anyhow::anyhow!("Unsupported dump version {}", version))
-        .with_context(file_ctx)?
-    }
+    let (mut input, _) = decompress(input).await.with_context(file_ctx)?;
+    read_version(&mut input).await.with_context(file_ctx)?;
     let mut buf = BytesMut::with_capacity(65536);
     let header = read_packet(&mut input, &mut buf, Header).await
         .with_context(file_ctx)?
         .ok_or_else(|| anyhow::anyhow!("Dump is empty"))
                        .with_context(file_ctx)?;
-    cli.restore(header, Packets {
+    let progress = Progress::new(progress, bars, cli.database().to_string(),
+                                 total, read);
+    let mut packets = Packets {
         input: &mut input,
         buf,
-    }).await?;
+        progress,
+    };
+    cli.restore(header, &mut packets).await?;
+    packets.progress.finish();
     Ok(())
 }
 
@@ -189,8 +494,12 @@ fn path_to_database_name(path: &Path) -> anyhow::Result<String> {
     Ok(decoded.to_string())
 }
 
-async fn apply_init(cli: &mut Connection, path: &Path) -> anyhow::Result<()> {
-    let mut input = fs::File::open(path).await?;
+async fn apply_init(cli: &mut Connection, path: &Path,
+                    decrypt_options: &DecryptOptions)
+    -> anyhow::Result<()>
+{
+    let input = Box::new(fs::File::open(path).await?) as Input;
+    let (mut input, _) = decrypt(input, decrypt_options).await?;
     let mut inbuf = BytesMut::with_capacity(8192);
     log::debug!("Restoring init script");
     loop {
@@ -215,17 +524,24 @@ pub async fn restore_all<'x>(cli: &mut Connection, options: &Options,
     -> anyhow::Result<()>
 {
     let dir = &params.path;
-    let filename = dir.join("init.edgeql");
-    apply_init(cli, filename.as_ref()).await
-        .with_context(|| format!("error applying init file {:?}", filename))?;
+    if !params.skip_init {
+        let filename = dir.join("init.edgeql");
+        apply_init(cli, filename.as_ref(), &params.decrypt).await
+            .with_context(|| format!("error applying init file {:?}",
+                                     filename))?;
+    }
+    if params.only_init {
+        return Ok(());
+    }
+    let selection = Selection::new(&params.databases)?;
 
     let mut conn_params = options.conn_params.clone();
     conn_params.wait_until_available(Duration::from_secs(300));
-    let mut params = params.clone();
     let dbs = list_databases::get_databases(cli).await?;
     let existing: BTreeSet<_> = dbs.into_iter().collect();
 
     let dump_ext = OsString::from("dump");
+    let mut dumps = Vec::new();
     let mut dir_list = fs::read_dir(&dir).await?;
     while let Some(entry) = dir_list.next_entry().await? {
         let path = entry.path();
@@ -233,19 +549,72 @@ pub async fn restore_all<'x>(cli: &mut Connection, options: &Options,
             continue;
         }
         let database = path_to_database_name(&path)?;
-        log::debug!("Restoring database {:?}", database);
-        if !existing.contains(&database) {
-            let stmt = format!("CREATE DATABASE {}", quote_name(&database));
+        if !selection.matches(&database) {
+            log::debug!("Skipping database {:?}", database);
+            continue;
+        }
+        dumps.push((database, path));
+    }
+    if dumps.is_empty() {
+        print::warn("No databases match the specified patterns.");
+    }
+    for rename in &params.rename {
+        let Some(item) = dumps.iter_mut()
+            .find(|(name, _)| name == &rename.old_name)
+            else {
+                anyhow::bail!("cannot rename {:?}: the database is not \
+                               restored from {:?}", rename.old_name, dir);
+            };
+        item.0 = rename.new_name.clone();
+    }
+    dumps.sort();
+    if let Some(dup) = dumps.windows(2).find(|pair| pair[0].0 == pair[1].0) {
+        anyhow::bail!("multiple dumps would be restored into database {:?}",
+                      dup[0].0);
+    }
+
+    let mut params = params.clone();
+    let replaced = dumps.iter()
+        .filter(|(name, _)| existing.contains(name))
+        .map(|(name, _)| &name[..])
+        .collect::<Vec<_>>();
+    if params.replace && !params.non_interactive && !replaced.is_empty() {
+        let q = question::Confirm::new_dangerous(
+            format!("Do you really want to wipe the contents of \
+                    the databases {}?", replaced.join(", "))
+        );
+        if !cli.ping_while(q.async_ask()).await? {
+            print::error("Canceled.");
+            return Err(ExitCode::new(exit_codes::NOT_CONFIRMED).into());
+        }
+        // already confirmed for all the databases
+        params.non_interactive = true;
+    }
+    for (database, _) in &dumps {
+        if !existing.contains(database) {
+            let stmt = format!("CREATE DATABASE {}", quote_name(database));
             cli.execute(&stmt, &()).await
                 .with_context(|| format!("error creating database {:?}",
                                          database))?;
         }
-        conn_params.database(&database)?;
-        let mut db_conn = conn_params.connect().await.with_context(||
-             format!("cannot connect to database {:?}", database))?;
-        params.path = path.into();
-        restore_db(&mut db_conn, options, &params).await
-            .with_context(|| format!("restoring database {:?}", database))?;
     }
-    Ok(())
+
+    let bars = MultiProgress::new();
+    let jobs = usize::from(params.databases.jobs.unwrap_or(1));
+    stream::iter(dumps.into_iter().map(Ok::<_, anyhow::Error>))
+        .try_for_each_concurrent(jobs, |(database, path)| {
+            let mut conn_params = conn_params.clone();
+            let mut params = params.clone();
+            params.path = path;
+            let bars = &bars;
+            async move {
+                log::debug!("Restoring database {:?}", database);
+                conn_params.database(&database)?;
+                let mut db_conn = conn_params.connect().await.with_context(||
+                     format!("cannot connect to database {:?}", database))?;
+                restore_db(&mut db_conn, options, &params, bars).await
+                    .with_context(|| format!("restoring database {:?}",
+                                             database))
+            }
+        }).await
 }
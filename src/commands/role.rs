@@ -0,0 +1,233 @@
+use std::path::Path;
+
+use anyhow::Context;
+use edgeql_parser::helpers::{quote_name, quote_string};
+use prettytable::{Table, Row, Cell};
+
+use crate::commands::list;
+use crate::commands::parser::{CreateRole, AlterRole, DropRole, ShowRole};
+use crate::commands::parser::RolePassword;
+use crate::commands::{ExitCode, Options};
+use crate::connect::Connection;
+use crate::credentials;
+use crate::hint::HintExt;
+use crate::portable::exit_codes;
+use crate::portable::local::is_valid_local_instance_name;
+use crate::portable::reset_password::{ask_new_password, generate_password};
+use crate::print;
+use crate::question;
+use crate::table;
+use crate::tty_password;
+
+
+#[derive(serde::Deserialize, serde::Serialize, Debug)]
+struct RoleInfo {
+    name: String,
+    superuser: bool,
+    member_of: Vec<String>,
+    has_password: bool,
+}
+
+/// Result of `role create` and `role alter` in JSON format
+#[derive(serde::Serialize, Debug)]
+struct Changed<'a> {
+    name: &'a str,
+    /// Only present if password was generated
+    #[serde(skip_serializing_if="Option::is_none")]
+    password: Option<&'a str>,
+    #[serde(skip_serializing_if="Option::is_none")]
+    credentials_file: Option<&'a Path>,
+}
+
+/// Returns new password (if any) and whether it was generated
+fn new_password(role: &str, options: &RolePassword, generate: bool)
+    -> anyhow::Result<Option<(String, bool)>>
+{
+    if options.password_from_stdin {
+        Ok(Some((tty_password::read_stdin()?, false)))
+    } else if options.password {
+        Ok(Some((ask_new_password(role)?, false)))
+    } else if options.generate_password || generate {
+        Ok(Some((generate_password(), true)))
+    } else {
+        Ok(None)
+    }
+}
+
+fn print_changed(name: &str, password: &Option<(String, bool)>,
+                 credentials_file: Option<&Path>, json: bool)
+    -> anyhow::Result<()>
+{
+    let generated = password.as_ref()
+        .filter(|(_, generated)| *generated)
+        .map(|(password, _)| &password[..]);
+    if json {
+        return list::print_json(&Changed {
+            name,
+            // password is not needed in the output if it's saved
+            password: generated.filter(|_| credentials_file.is_none()),
+            credentials_file,
+        });
+    }
+    if let Some(path) = credentials_file {
+        print::success_msg("Credentials saved to", path.display());
+    } else if let Some(password) = generated {
+        println!("{}", password);
+    }
+    Ok(())
+}
+
+pub async fn create(cli: &mut Connection, cmd: &CreateRole, options: &Options)
+    -> anyhow::Result<()>
+{
+    let creds_path = match &cmd.save_credentials {
+        Some(name) => {
+            if !is_valid_local_instance_name(name) {
+                return Err(anyhow::anyhow!("invalid instance name {:?}", name))
+                    .hint("Instance name must be a valid identifier, \
+                           (regex: ^[a-zA-Z_0-9]+(-[a-zA-Z_0-9]+)*$)")?;
+            }
+            let path = credentials::path(name)?;
+            if path.exists() {
+                return Err(anyhow::anyhow!("credentials file {} exists",
+                                           path.display()))
+                    .hint("Choose a different instance name \
+                           or run `edgedb instance unlink` first")?;
+            }
+            Some(path)
+        }
+        None => None,
+    };
+    let password = new_password(&cmd.role_name, &cmd.password,
+                                creds_path.is_some())?;
+    let extending = if cmd.extending.is_empty() {
+        String::new()
+    } else {
+        let roles = cmd.extending.iter().map(|r| quote_name(r))
+            .collect::<Vec<_>>().join(", ");
+        format!(" EXTENDING {}", roles)
+    };
+    let block = match &password {
+        Some((password, _)) => {
+            format!(" {{ SET password := {}; }}", quote_string(password))
+        }
+        None => String::new(),
+    };
+    let status = cli.execute(
+        &format!("CREATE {superuser}ROLE {name}{extending}{block}",
+                 superuser=if cmd.superuser { "SUPERUSER " } else { "" },
+                 name=quote_name(&cmd.role_name),
+                 extending=extending,
+                 block=block),
+        &(),
+    ).await?;
+    if let Some(path) = &creds_path {
+        let config = options.conn_params.get()?;
+        let mut creds = config.as_credentials()?;
+        creds.user = cmd.role_name.clone();
+        creds.password = password.as_ref().map(|(p, _)| p.clone());
+        creds.database = Some(cli.database().into());
+        credentials::write_async(path, &creds).await?;
+    }
+    if !cmd.json {
+        print::completion(&status);
+    }
+    print_changed(&cmd.role_name, &password, creds_path.as_deref(), cmd.json)
+}
+
+pub async fn alter(cli: &mut Connection, cmd: &AlterRole, _: &Options)
+    -> anyhow::Result<()>
+{
+    let password = new_password(&cmd.role_name, &cmd.password, false)?;
+    let mut commands = Vec::new();
+    for role in &cmd.extending {
+        commands.push(format!("EXTENDING {};", quote_name(role)));
+    }
+    for role in &cmd.drop_extending {
+        commands.push(format!("DROP EXTENDING {};", quote_name(role)));
+    }
+    if let Some((password, _)) = &password {
+        commands.push(format!("SET password := {};", quote_string(password)));
+    }
+    if let Some(new_name) = &cmd.rename {
+        commands.push(format!("RENAME TO {};", quote_name(new_name)));
+    }
+    if commands.is_empty() {
+        return Err(anyhow::anyhow!("nothing to alter"))
+            .hint("Specify at least one of `--rename`, `--extending`, \
+                   `--drop-extending` or one of the password options")?;
+    }
+    let status = cli.execute(
+        &format!("ALTER ROLE {} {{ {} }}",
+                 quote_name(&cmd.role_name), commands.join(" ")),
+        &(),
+    ).await?;
+    if !cmd.json {
+        print::completion(&status);
+    }
+    let name = cmd.rename.as_ref().unwrap_or(&cmd.role_name);
+    print_changed(name, &password, None, cmd.json)
+}
+
+pub async fn drop(cli: &mut Connection, cmd: &DropRole, _: &Options)
+    -> anyhow::Result<()>
+{
+    if !cmd.non_interactive {
+        let q = question::Confirm::new_dangerous(
+            format!("Do you really want to delete role {:?}?", cmd.role_name)
+        );
+        if !cli.ping_while(q.async_ask()).await? {
+            print::error("Canceled.");
+            return Err(ExitCode::new(exit_codes::NOT_CONFIRMED).into());
+        }
+    }
+    let status = cli.execute(
+        &format!("DROP ROLE {}", quote_name(&cmd.role_name)),
+        &(),
+    ).await?;
+    print::completion(&status);
+    Ok(())
+}
+
+pub async fn show(cli: &mut Connection, cmd: &ShowRole, _: &Options)
+    -> anyhow::Result<()>
+{
+    let data = cli.query_single::<String, _>(r###"
+        SELECT to_str(<json>(
+            SELECT sys::Role {
+                name,
+                superuser := .is_superuser,
+                member_of := array_agg(.member_of.name),
+                has_password := EXISTS .password,
+            }
+            FILTER .name = <str>$0
+        ))
+    "###, &(&cmd.role_name[..],)).await?;
+    let Some(data) = data else {
+        return Err(anyhow::anyhow!("role {:?} does not exist",
+                                   cmd.role_name))
+            .hint("Run `edgedb list roles` to see existing roles")?;
+    };
+    let mut role: RoleInfo = serde_json::from_str(&data)
+        .context("cannot decode role")?;
+    role.member_of.sort();
+    if cmd.json {
+        return list::print_json(&role);
+    }
+    let mut table = Table::new();
+    table.set_format(*table::FORMAT);
+    let rows = [
+        ("Name", role.name.clone()),
+        ("Superuser", role.superuser.to_string()),
+        ("Member of", role.member_of.join(", ")),
+        ("Password set", role.has_password.to_string()),
+    ];
+    for (title, value) in rows {
+        table.add_row(Row::new(vec![
+            table::header_cell(title),
+            Cell::new(&value),
+        ]));
+    }
+    table.printstd();
+    Ok(())
+}
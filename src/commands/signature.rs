@@ -0,0 +1,98 @@
+use std::fmt::Write;
+
+use anyhow::Context;
+
+
+/// Shape of a single parameter, serialized to JSON by the queries in
+/// `list functions`, `list operators` and `list constraints`
+pub const PARAMS_JSON: &str = r###"
+    to_str(<json>array_agg(.params {
+        num,
+        name,
+        kind := <str>.kind,
+        typemod := <str>.typemod,
+        type_name := .type.name,
+        `default`,
+    }))
+"###;
+
+#[derive(serde::Deserialize, serde::Serialize, Debug)]
+pub struct Param {
+    pub num: i64,
+    pub name: String,
+    pub kind: String,
+    pub typemod: String,
+    pub type_name: String,
+    pub default: Option<String>,
+}
+
+pub fn parse_params(json: &str) -> anyhow::Result<Vec<Param>> {
+    let mut params: Vec<Param> = serde_json::from_str(json)
+        .context("cannot decode parameters")?;
+    params.sort_by_key(|p| p.num);
+    Ok(params)
+}
+
+pub fn typemod(typemod: &str) -> &'static str {
+    match typemod {
+        "SetOfType" => "set of ",
+        "OptionalType" => "optional ",
+        _ => "",
+    }
+}
+
+fn param_kind(kind: &str) -> &'static str {
+    match kind {
+        "VariadicParam" => "variadic ",
+        "NamedOnlyParam" => "named only ",
+        _ => "",
+    }
+}
+
+/// Formats signature like `(a: int64, named only b: optional str = 'x')`
+pub fn params(params: &[Param]) -> String {
+    let mut buf = String::from("(");
+    for (idx, param) in params.iter().enumerate() {
+        if idx > 0 {
+            buf.push_str(", ");
+        }
+        write!(&mut buf, "{}{}: {}{}",
+            param_kind(&param.kind), param.name,
+            typemod(&param.typemod), param.type_name).unwrap();
+        if let Some(default) = &param.default {
+            write!(&mut buf, " = {}", default).unwrap();
+        }
+    }
+    buf.push(')');
+    buf
+}
+
+/// Formats full signature including return type and its cardinality
+pub fn full(params: &[Param], return_typemod: &str, return_type: &str)
+    -> String
+{
+    format!("{} -> {}{}", self::params(params),
+            typemod(return_typemod), return_type)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{parse_params, full};
+
+    #[test]
+    fn signature() {
+        let params = parse_params(r#"[
+            {"num": 1, "name": "opt", "kind": "NamedOnlyParam",
+             "typemod": "OptionalType", "type_name": "std::str",
+             "default": "'x'"},
+            {"num": 0, "name": "items", "kind": "PositionalParam",
+             "typemod": "SetOfType", "type_name": "std::int64",
+             "default": null}
+        ]"#).unwrap();
+        assert_eq!(full(&params, "SingletonType", "std::bool"),
+            "(items: set of std::int64, \
+              named only opt: optional std::str = 'x') -> std::bool");
+        assert_eq!(full(&[], "OptionalType", "std::str"),
+            "() -> optional std::str");
+    }
+}
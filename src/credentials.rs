@@ -44,9 +44,15 @@ pub fn all_instance_names() -> anyhow::Result<BTreeSet<String>> {
 }
 
 #[tokio::main]
-#[context("cannot write credentials file {}", path.display())]
 pub async fn write(path: &Path, credentials: &Credentials)
     -> anyhow::Result<()>
+{
+    write_async(path, credentials).await
+}
+
+#[context("cannot write credentials file {}", path.display())]
+pub async fn write_async(path: &Path, credentials: &Credentials)
+    -> anyhow::Result<()>
 {
     use tokio::fs;
 
@@ -4,16 +4,223 @@ use std::str;
 use codespan_reporting::files::SimpleFile;
 use codespan_reporting::diagnostic::{Diagnostic, Label, LabelStyle};
 use codespan_reporting::term::{emit};
+use once_cell::sync::Lazy;
+use regex::Regex;
 use termcolor::{StandardStream, ColorChoice};
 
-use edgedb_errors::{Error, InternalServerError};
+use edgedb_errors::{Error, InternalServerError, InvalidReferenceError};
 
+use crate::connect::Connection;
 use crate::print;
 
 
+static UNKNOWN_NAME: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"'([^']+)' does not exist").unwrap()
+});
+static UNKNOWN_POINTER: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"object type '([^']+)' has no link or property '([^']+)'")
+        .unwrap()
+});
+
+
+#[derive(Debug, Clone, Copy)]
+pub struct Position {
+    /// Byte offset from the start of the source
+    pub offset: usize,
+    /// One-based line number
+    pub line: usize,
+    /// One-based column in characters
+    pub column: usize,
+}
+
+#[derive(serde::Serialize)]
+struct Span {
+    start: usize,
+    end: usize,
+    line: usize,
+    col: usize,
+    end_line: usize,
+    end_col: usize,
+}
+
+#[derive(serde::Serialize)]
+struct ErrorJson<'a> {
+    #[serde(rename="type")]
+    kind: &'a str,
+    code: u32,
+    message: &'a str,
+    #[serde(skip_serializing_if="Option::is_none")]
+    hint: Option<&'a str>,
+    #[serde(skip_serializing_if="Option::is_none")]
+    details: Option<&'a str>,
+    filename: &'a str,
+    statement: Span,
+    #[serde(skip_serializing_if="Option::is_none")]
+    context: Option<Span>,
+}
+
+impl Position {
+    pub fn new() -> Position {
+        Position { offset: 0, line: 1, column: 1 }
+    }
+    pub fn advance(&self, text: &str) -> Position {
+        let mut pos = *self;
+        pos.offset += text.len();
+        for c in text.chars() {
+            if c == '\n' {
+                pos.line += 1;
+                pos.column = 1;
+            } else {
+                pos.column += 1;
+            }
+        }
+        pos
+    }
+    fn span(&self, text: &str, start: usize, end: usize) -> Span {
+        let start = clamp_to_char(text, start);
+        let end = clamp_to_char(text, end).max(start);
+        let begin = self.advance(&text[..start]);
+        let finish = begin.advance(&text[start..end]);
+        Span {
+            start: begin.offset,
+            end: finish.offset,
+            line: begin.line,
+            col: begin.column,
+            end_line: finish.line,
+            end_col: finish.column,
+        }
+    }
+}
+
+fn clamp_to_char(text: &str, mut pos: usize) -> usize {
+    pos = pos.min(text.len());
+    while !text.is_char_boundary(pos) {
+        pos -= 1;
+    }
+    pos
+}
+
+/// Returns byte range of the statement without surrounding whitespace and
+/// comments
+fn code_range(text: &str) -> (usize, usize) {
+    let mut start = text.len();
+    let mut in_comment = false;
+    for (idx, c) in text.char_indices() {
+        match c {
+            '\n' if in_comment => in_comment = false,
+            _ if in_comment => {}
+            '#' => in_comment = true,
+            c if c.is_whitespace() => {}
+            _ => {
+                start = idx;
+                break;
+            }
+        }
+    }
+    let end = text.trim_end().len().max(start);
+    (start, end)
+}
+
+pub fn print_query_error_json(err: &Error, query: &str,
+                              source_name: &str, position: Position,
+                              suggestion: Option<&str>)
+    -> Result<(), anyhow::Error>
+{
+    let (stmt_start, stmt_end) = code_range(query);
+    let context = match (err.position_start(), err.position_end()) {
+        (Some(s), Some(e)) => Some(position.span(query, s, e)),
+        _ => None,
+    };
+    let hint = match (err.hint(), suggestion) {
+        (Some(hint), Some(suggestion)) => Some(format!("{hint}; {suggestion}")),
+        (hint, suggestion) => hint.or(suggestion).map(|h| h.to_owned()),
+    };
+    let error = ErrorJson {
+        kind: err.kind_name(),
+        code: err.code(),
+        message: err.initial_message().unwrap_or(""),
+        hint: hint.as_deref(),
+        details: err.details(),
+        filename: source_name,
+        statement: position.span(query, stmt_start, stmt_end),
+        context,
+    };
+    eprintln!("{}", serde_json::to_string(&error)?);
+    Ok(())
+}
+
+/// Finds schema names similar to the one mentioned in the
+/// `InvalidReferenceError` and returns a hint text
+pub async fn suggest_names(cli: &mut Connection, err: &Error)
+    -> Option<String>
+{
+    if !err.is::<InvalidReferenceError>() {
+        return None;
+    }
+    let message = err.initial_message()?;
+    let pointer = UNKNOWN_POINTER.captures(message);
+    let (name, candidates) = if let Some(caps) = pointer {
+        let names = cli.query::<String, _>(r###"
+            SELECT (
+                SELECT schema::ObjectType FILTER .name = <str>$0
+            ).pointers.name
+        "###, &(&caps[1],)).await;
+        (caps.get(2)?.as_str(), names)
+    } else if let Some(caps) = UNKNOWN_NAME.captures(message) {
+        let names = cli.query::<String, _>(r###"
+            SELECT DISTINCT (
+                SELECT schema::Object FILTER .name LIKE '%::%'
+            ).name
+        "###, &()).await;
+        (caps.get(1)?.as_str(), names)
+    } else {
+        return None;
+    };
+    let candidates = match candidates {
+        Ok(candidates) => candidates,
+        Err(e) => {
+            // e.g. a transaction is aborted, so schema can't be fetched
+            log::debug!("Cannot fetch schema names: {:#}", e);
+            return None;
+        }
+    };
+    best_match(name, &candidates)
+        .map(|name| format!("did you mean `{}`?", name))
+}
+
+fn best_match<'x>(name: &str, candidates: &'x [String]) -> Option<&'x str> {
+    let qualified = name.contains("::");
+    let mut best = None::<(f64, &str)>;
+    for candidate in candidates {
+        let short = if qualified {
+            &candidate[..]
+        } else {
+            candidate.rsplit("::").next().unwrap_or(&candidate[..])
+        };
+        if candidate == name {
+            continue;
+        }
+        let confidence = strsim::jaro_winkler(name, short);
+        if confidence <= 0.8 {
+            continue;
+        }
+        if best.map(|(c, _)| confidence > c).unwrap_or(true) {
+            best = Some((confidence, &candidate[..]));
+        }
+    }
+    best.map(|(_, name)| name)
+}
+
 pub fn print_query_error(err: &Error, query: &str, verbose: bool,
                          source_name: &str)
     -> Result<(), anyhow::Error>
+{
+    print_query_error_with_suggestion(err, query, verbose, source_name, None)
+}
+
+pub fn print_query_error_with_suggestion(err: &Error, query: &str,
+    verbose: bool, source_name: &str, suggestion: Option<&str>)
+    -> Result<(), anyhow::Error>
 {
     let pstart = err.position_start();
     let pend = err.position_end();
@@ -21,11 +228,17 @@ pub fn print_query_error(err: &Error, query: &str, verbose: bool,
         (Some(s), Some(e)) => (s, e),
         _ => {
             print::edgedb_error(&err, verbose);
+            if let Some(suggestion) = suggestion {
+                eprintln!("  Hint: {}", suggestion);
+            }
             return Ok(());
         }
     };
     let hint = err.hint().unwrap_or("error");
-    let detail = err.details().map(|s| s.into());
+    let notes = err.details().into_iter()
+        .chain(suggestion)
+        .map(|s| s.to_string())
+        .collect::<Vec<_>>();
     let files = SimpleFile::new(source_name, query);
     let context_error = err
         .contexts()
@@ -51,7 +264,7 @@ pub fn print_query_error(err: &Error, query: &str, verbose: bool,
                 message: hint.into(),
             },
         ])
-        .with_notes(detail.into_iter().collect());
+        .with_notes(notes);
 
     emit(&mut StandardStream::stderr(ColorChoice::Auto),
         &Default::default(), &files, &diag)?;
@@ -66,3 +279,42 @@ pub fn print_query_error(err: &Error, query: &str, verbose: bool,
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod test {
+    use super::best_match;
+
+    fn names(list: &[&str]) -> Vec<String> {
+        list.iter().map(|x| x.to_string()).collect()
+    }
+
+    #[test]
+    fn qualified() {
+        let names = names(&["default::User", "default::Post"]);
+        assert_eq!(best_match("default::Usr", &names), Some("default::User"));
+        // module is a part of the compared name
+        assert_eq!(best_match("blog::Usr", &names), None);
+    }
+
+    #[test]
+    fn unqualified() {
+        let names = names(&["default::User", "Usr::Post"]);
+        assert_eq!(best_match("Usr", &names), Some("default::User"));
+        assert_eq!(best_match("User", &names), Some("default::User"));
+    }
+
+    #[test]
+    fn threshold() {
+        assert_eq!(best_match("Movie", &names(&["default::Model"])), None);
+        assert_eq!(best_match("Movie", &names(&["default::Mode"])),
+                   Some("default::Mode"));
+    }
+
+    #[test]
+    fn skip_exact() {
+        let names = names(&["default::User", "default::Users"]);
+        assert_eq!(best_match("default::User", &names),
+                   Some("default::Users"));
+        assert_eq!(best_match("default::User", &names[..1]), None);
+    }
+}
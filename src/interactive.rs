@@ -27,7 +27,9 @@ use crate::commands::{backslash, ExitCode};
 use crate::config::Config;
 use crate::credentials;
 use crate::echo;
-use crate::error_display::print_query_error;
+use crate::error_display::{print_query_error, suggest_names};
+use crate::error_display::print_query_error_with_suggestion;
+use crate::hint::HintedError;
 use crate::interrupt::{Interrupt, InterruptError};
 use crate::options::Options;
 use crate::outputs::tab_separated;
@@ -249,6 +251,11 @@ async fn execute_backslash(mut state: &mut repl::State, text: &str)
                 // messages, so ignoring it is safe
             } else {
                 eprintln!("Error executing command: {:#}", e);
+                for item in e.chain() {
+                    if let Some(e) = item.downcast_ref::<HintedError>() {
+                        eprintln!("  Hint: {}", e.hint);
+                    }
+                }
                 // Quick-edit command on error
                 state.initial_text = text.into();
                 state.last_error = Some(e);
@@ -333,16 +340,20 @@ async fn execute_query(options: &Options, state: &mut repl::State,
                     return Err(RetryStateError)?;
                 }
                 Err(e) => {
-                    print_query_error(&e, statement,
+                    let suggestion = suggest_names(cli, &e).await;
+                    print_query_error_with_suggestion(&e, statement,
                                       state.verbose_errors,
-                                      "<query>")?;
+                                      "<query>",
+                                      suggestion.as_deref())?;
                     return Err(QueryError)?;
                 }
             }
         }
         Err(e) if e.is::<StateMismatchError>() => return Err(RetryStateError)?,
         Err(e) => {
-            print_query_error(&e, statement, state.verbose_errors, "<query>")?;
+            let suggestion = suggest_names(cli, &e).await;
+            print_query_error_with_suggestion(&e, statement,
+                state.verbose_errors, "<query>", suggestion.as_deref())?;
             return Err(QueryError)?;
         }
     };
@@ -18,8 +18,10 @@ use tokio_stream::StreamExt;
 use crate::classify;
 use crate::commands::ExitCode;
 use crate::connect::Connection;
-use crate::error_display::print_query_error;
-use crate::options::Options;
+use crate::error_display::{print_query_error_json, suggest_names};
+use crate::error_display::print_query_error_with_suggestion;
+use crate::error_display::Position;
+use crate::options::{ErrorFormat, Options};
 use crate::options::Query;
 use crate::outputs::tab_separated;
 use crate::print::{self, PrintError};
@@ -27,6 +29,12 @@ use crate::repl::OutputFormat;
 use crate::statement::{read_statement, EndOfFile};
 
 
+struct Source<'a> {
+    name: &'a str,
+    start: Position,
+    error_format: ErrorFormat,
+}
+
 #[tokio::main]
 pub async fn noninteractive_main(q: &Query, options: &Options)
     -> Result<(), anyhow::Error>
@@ -51,10 +59,12 @@ pub async fn noninteractive_main(q: &Query, options: &Options)
 
     if let Some(filename) = &q.file {
         if filename == "-" {
-            interpret_file(&mut stdin(), options, fmt).await?;
+            interpret_file(&mut stdin(), options, fmt,
+                           "<stdin>", q.error_format).await?;
         } else {
             let mut file = AsyncFile::open(filename).await?;
-            interpret_file(&mut file, options, fmt).await?;
+            interpret_file(&mut file, options, fmt,
+                           filename, q.error_format).await?;
         }
     } else if let Some(queries) = &q.queries {
         let mut conn = options.create_connector().await?.connect().await?;
@@ -63,7 +73,12 @@ pub async fn noninteractive_main(q: &Query, options: &Options)
                 anyhow::bail!("Analyze queries are not allowed. \
                                Use the dedicated `edgedb analyze` command.");
             }
-            run_query(&mut conn, query, &options, fmt).await?;
+            let source = Source {
+                name: "<query>",
+                start: Position::new(),
+                error_format: q.error_format,
+            };
+            run_query(&mut conn, query, &options, fmt, &source).await?;
         }
     } else {
         print::error("either a --file option or \
@@ -77,15 +92,18 @@ pub async fn noninteractive_main(q: &Query, options: &Options)
 pub async fn interpret_stdin(options: &Options, fmt: OutputFormat)
     -> Result<(), anyhow::Error>
 {
-    return interpret_file(&mut stdin(), options, fmt).await;
+    return interpret_file(&mut stdin(), options, fmt,
+                          "<stdin>", ErrorFormat::Human).await;
 }
 
-async fn interpret_file<T>(file: &mut T, options: &Options, fmt: OutputFormat)
+async fn interpret_file<T>(file: &mut T, options: &Options, fmt: OutputFormat,
+                           name: &str, error_format: ErrorFormat)
     -> Result<(), anyhow::Error>
     where T: AsyncRead + Unpin
 {
     let mut conn = options.create_connector().await?.connect().await?;
     let mut inbuf = BytesMut::with_capacity(8192);
+    let mut position = Position::new();
     loop {
         let stmt = match read_statement(&mut inbuf, file).await {
             Ok(chunk) => chunk,
@@ -94,32 +112,50 @@ async fn interpret_file<T>(file: &mut T, options: &Options, fmt: OutputFormat)
         };
         let stmt = str::from_utf8(&stmt[..])
             .context("can't decode statement")?;
+        let source = Source {
+            name,
+            start: position,
+            error_format,
+        };
+        position = position.advance(stmt);
         if preparser::is_empty(stmt) {
             continue;
         }
         if classify::is_analyze(stmt) {
             anyhow::bail!("Analyze queries are not allowed. \
-                           Use the dedicated `edgedb analyze` command.");
+                           Use the dedicated `edgedb analyze --file` command.");
         }
-        run_query(&mut conn, &stmt, &options, fmt).await?;
+        run_query(&mut conn, &stmt, &options, fmt, &source).await?;
     }
     Ok(())
 }
 
 async fn run_query(conn: &mut Connection, stmt: &str, options: &Options,
-    fmt: OutputFormat)
+    fmt: OutputFormat, source: &Source<'_>)
     -> Result<(), anyhow::Error>
 {
-    _run_query(conn, stmt, options, fmt).await.map_err(|err| {
-        if let Some(err) = err.downcast_ref::<edgedb_errors::Error>() {
-            match print_query_error(&err, stmt, false, "<query>") {
-                Ok(()) => ExitCode::new(1).into(),
-                Err(e) => e,
-            }
-        } else {
-            err
+    let err = match _run_query(conn, stmt, options, fmt).await {
+        Ok(()) => return Ok(()),
+        Err(err) => err,
+    };
+    let Some(err) = err.downcast_ref::<edgedb_errors::Error>() else {
+        return Err(err);
+    };
+    let suggestion = suggest_names(conn, err).await;
+    let res = match source.error_format {
+        ErrorFormat::Human => {
+            print_query_error_with_suggestion(&err, stmt, false, source.name,
+                                              suggestion.as_deref())
+        }
+        ErrorFormat::Json => {
+            print_query_error_json(&err, stmt, source.name, source.start,
+                                   suggestion.as_deref())
         }
-    })
+    };
+    match res {
+        Ok(()) => Err(ExitCode::new(1).into()),
+        Err(e) => Err(e),
+    }
 }
 
 async fn _run_query(conn: &mut Connection, stmt: &str, _options: &Options,
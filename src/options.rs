@@ -363,9 +363,22 @@ pub struct Query {
     #[arg(short='f', long)]
     pub file: Option<String>,
 
+    /// Error format: `human` (default) or `json`. JSON errors are printed
+    /// to stderr, one per line.
+    #[arg(long, value_enum, default_value="human")]
+    pub error_format: ErrorFormat,
+
     pub queries: Option<Vec<String>>,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(clap::ValueEnum)]
+#[value(rename_all="kebab-case")]
+pub enum ErrorFormat {
+    Human,
+    Json,
+}
+
 #[derive(clap::Args, Clone, Debug)]
 pub struct UI {
     #[command(flatten)]
@@ -0,0 +1,389 @@
+use std::collections::BTreeSet;
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+
+use anyhow::Context;
+use fs_err as fs;
+use indicatif::HumanBytes;
+
+use crate::commands::{self, ExitCode};
+use crate::commands::parser::{ProgressFormat, Restore};
+use crate::connect::{Connection, Connector};
+use crate::format;
+use crate::hint::HintExt;
+use crate::portable::control;
+use crate::portable::exit_codes;
+use crate::portable::linux;
+use crate::portable::local::{InstanceInfo, Paths, write_json};
+use crate::portable::options::{Backup, BackupCommand, BackupCreate};
+use crate::portable::options::{BackupList, BackupRestore, BackupPrune};
+use crate::portable::options::{BackupRetention, InstanceName};
+use crate::print;
+use crate::question;
+use crate::table::{self, Table, Row, Cell};
+
+
+/// Metadata stored in `backup.json` of each backup directory
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+pub struct BackupInfo {
+    /// Name of the backup directory
+    #[serde(default)]
+    pub id: String,
+    #[serde(with="humantime_serde")]
+    pub timestamp: SystemTime,
+    pub server_version: String,
+    /// Total size of the dump files in bytes
+    pub size: u64,
+    /// Backup is created by the timer installed with `--schedule`
+    pub scheduled: bool,
+}
+
+pub fn backup(cmd: &Backup) -> anyhow::Result<()> {
+    use BackupCommand::*;
+
+    match &cmd.subcommand {
+        Create(c) => create(c),
+        List(c) => list(c),
+        Restore(c) => restore(c),
+        Prune(c) => prune(c),
+    }
+}
+
+fn local_name(name: &InstanceName) -> anyhow::Result<&str> {
+    match name {
+        InstanceName::Local(_) if cfg!(windows) => {
+            print::error("Backups are not supported on Windows yet.");
+            Err(ExitCode::new(1))?
+        }
+        InstanceName::Local(name) => Ok(name),
+        InstanceName::Cloud { .. } => {
+            print::error("This operation is not supported on cloud instances yet.");
+            Err(ExitCode::new(1))?
+        }
+    }
+}
+
+fn create(cmd: &BackupCreate) -> anyhow::Result<()> {
+    let name = local_name(&cmd.instance)?;
+    if cmd.unschedule {
+        return unschedule(name);
+    }
+    if let Some(calendar) = &cmd.schedule {
+        return schedule(name, calendar, &cmd.retention);
+    }
+    let inst = InstanceInfo::read(name)?;
+    let paths = Paths::get(name)?;
+    let timestamp = SystemTime::now();
+    // colons are not allowed in file names on some systems
+    let id = humantime::format_rfc3339_seconds(timestamp).to_string()
+        .replace(':', "-");
+    let dir = paths.backups_dir.join(&id);
+    if dir.exists() {
+        anyhow::bail!("backup {:?} already exists", id);
+    }
+    let tmp_dir = paths.backups_dir.join(format!("{}.tmp", id));
+    if tmp_dir.exists() {
+        fs::remove_dir_all(&tmp_dir)?;
+    }
+
+    log::info!("Ensuring instance is started");
+    control::do_start(&inst)?;
+    let server_version = match dump(&inst, &tmp_dir) {
+        Ok(version) => version,
+        Err(e) => {
+            fs::remove_dir_all(&tmp_dir).ok();
+            return Err(e);
+        }
+    };
+    let info = BackupInfo {
+        size: dir_size(&tmp_dir)?,
+        id,
+        timestamp,
+        server_version,
+        scheduled: cmd.scheduled,
+    };
+    write_json(&tmp_dir.join("backup.json"), "backup metadata", &info)?;
+    fs::rename(&tmp_dir, &dir)?;
+    print::success_msg("Backup created",
+                       format!("{} ({})", info.id, HumanBytes(info.size)));
+
+    if is_set(&cmd.retention) {
+        prune_backups(&paths.backups_dir, &cmd.retention, false)?;
+    }
+    Ok(())
+}
+
+fn list(cmd: &BackupList) -> anyhow::Result<()> {
+    let name = local_name(&cmd.instance)?;
+    let backups = read_backups(&Paths::get(name)?.backups_dir)?;
+    if cmd.json {
+        println!("{}", serde_json::to_string_pretty(&backups)?);
+        return Ok(());
+    }
+    if backups.is_empty() {
+        eprintln!("No backups found.");
+        return Ok(());
+    }
+    let mut table = Table::new();
+    table.set_format(*table::FORMAT);
+    table.set_titles(Row::new(
+        ["ID", "Created", "Server Version", "Size", "Kind"]
+        .iter().map(|x| table::header_cell(x)).collect()));
+    for backup in &backups {
+        table.add_row(Row::new(vec![
+            Cell::new(&backup.id),
+            Cell::new(&format::done_before(backup.timestamp).to_string()),
+            Cell::new(&backup.server_version),
+            Cell::new(&HumanBytes(backup.size).to_string()),
+            Cell::new(if backup.scheduled { "scheduled" } else { "manual" }),
+        ]));
+    }
+    table.printstd();
+    Ok(())
+}
+
+fn restore(cmd: &BackupRestore) -> anyhow::Result<()> {
+    let name = local_name(&cmd.instance)?;
+    let paths = Paths::get(name)?;
+    let backups = read_backups(&paths.backups_dir)?;
+    let backup = match &cmd.backup_id {
+        Some(id) => backups.iter().find(|b| &b.id == id)
+            .ok_or_else(|| anyhow::anyhow!("no backup {:?} of instance {:?}",
+                                           id, name))
+            .hint("Run `edgedb instance backup list` \
+                   to see available backups")?,
+        None => backups.first()
+            .ok_or_else(|| anyhow::anyhow!("instance {:?} has no backups",
+                                           name))?,
+    };
+    if !cmd.non_interactive {
+        let q = question::Confirm::new_dangerous(format!(
+            "Do you really want to replace databases of instance {:?} \
+             with the data from backup {} ({})?",
+            name, backup.id, format::done_before(backup.timestamp)));
+        if !q.ask()? {
+            print::error("Canceled.");
+            return Err(ExitCode::new(exit_codes::NOT_CONFIRMED))?;
+        }
+    }
+    let inst = InstanceInfo::read(name)?;
+    log::info!("Ensuring instance is started");
+    control::do_start(&inst)?;
+    restore_backup(&inst, &paths.backups_dir.join(&backup.id),
+                   cmd.with_init)?;
+    print::success_msg("Restored backup", &backup.id);
+    Ok(())
+}
+
+fn prune(cmd: &BackupPrune) -> anyhow::Result<()> {
+    let name = local_name(&cmd.instance)?;
+    if !is_set(&cmd.retention) {
+        return Err(anyhow::anyhow!("no retention options specified"))
+            .hint("Specify `--keep-last`, `--keep-daily` or both")?;
+    }
+    prune_backups(&Paths::get(name)?.backups_dir, &cmd.retention,
+                  cmd.dry_run)
+}
+
+fn schedule(name: &str, calendar: &str, retention: &BackupRetention)
+    -> anyhow::Result<()>
+{
+    if !cfg!(target_os="linux") {
+        anyhow::bail!("`--schedule` is only supported on Linux with systemd");
+    }
+    if calendar.is_empty() || calendar.chars().any(|c| c.is_control()) {
+        anyhow::bail!("invalid calendar event {:?}", calendar);
+    }
+    // make sure instance exists
+    InstanceInfo::read(name)?;
+    let mut args = Vec::new();
+    if let Some(keep_last) = retention.keep_last {
+        args.push(format!("--keep-last={}", keep_last));
+    }
+    if let Some(keep_daily) = retention.keep_daily {
+        args.push(format!("--keep-daily={}", keep_daily));
+    }
+    linux::create_backup_timer(name, calendar, &args)?;
+    print::success_msg("Scheduled backups",
+                       format!("instance {:?}, {}", name, calendar));
+    Ok(())
+}
+
+fn unschedule(name: &str) -> anyhow::Result<()> {
+    if !cfg!(target_os="linux") || !linux::remove_backup_timer(name)? {
+        anyhow::bail!("no backups are scheduled for instance {:?}", name);
+    }
+    print::success_msg("Removed backup timer of instance", name);
+    Ok(())
+}
+
+fn is_set(retention: &BackupRetention) -> bool {
+    retention.keep_last.is_some() || retention.keep_daily.is_some()
+}
+
+fn prune_backups(dir: &Path, retention: &BackupRetention, dry_run: bool)
+    -> anyhow::Result<()>
+{
+    let backups = read_backups(dir)?;
+    let pruned = to_prune(&backups,
+                          retention.keep_last.unwrap_or(0) as usize,
+                          retention.keep_daily.unwrap_or(0) as usize);
+    for backup in pruned {
+        if dry_run {
+            println!("{}", backup.id);
+        } else {
+            fs::remove_dir_all(dir.join(&backup.id))?;
+            print::success_msg("Removed backup", &backup.id);
+        }
+    }
+    Ok(())
+}
+
+/// Returns backups not kept by the retention options, `backups` must be
+/// sorted from the newest to the oldest. Days are counted in UTC
+fn to_prune(backups: &[BackupInfo], keep_last: usize, keep_daily: usize)
+    -> Vec<&BackupInfo>
+{
+    let mut days = BTreeSet::new();
+    let mut pruned = Vec::new();
+    for (idx, backup) in backups.iter().enumerate() {
+        let day = backup.timestamp.duration_since(SystemTime::UNIX_EPOCH)
+            .map(|d| d.as_secs() / 86400)
+            .unwrap_or(0);
+        // only the newest backup of the day is kept as a daily one
+        let daily = days.len() < keep_daily && days.insert(day);
+        if idx < keep_last || daily {
+            continue;
+        }
+        pruned.push(backup);
+    }
+    pruned
+}
+
+/// Reads metadata of all backups sorted from the newest to the oldest,
+/// unfinished and broken backups are skipped
+fn read_backups(dir: &Path) -> anyhow::Result<Vec<BackupInfo>> {
+    let mut backups = Vec::new();
+    if !dir.exists() {
+        return Ok(backups);
+    }
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let Some(id) = entry.file_name().to_str().map(String::from)
+            else { continue };
+        if id.ends_with(".tmp") {
+            continue;
+        }
+        let path = entry.path().join("backup.json");
+        let info = fs::read(&path).map_err(anyhow::Error::from)
+            .and_then(|data| serde_json::from_slice::<BackupInfo>(&data)
+                .with_context(|| format!("error decoding {:?}", path)));
+        match info {
+            Ok(info) => backups.push(BackupInfo { id, ..info }),
+            Err(e) => log::warn!("Skipping backup {:?}: {:#}", id, e),
+        }
+    }
+    backups.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
+    Ok(backups)
+}
+
+fn dir_size(dir: &Path) -> anyhow::Result<u64> {
+    let mut size = 0;
+    for entry in fs::read_dir(dir)? {
+        size += entry?.metadata()?.len();
+    }
+    Ok(size)
+}
+
+async fn connect(inst: &InstanceInfo)
+    -> anyhow::Result<(Connection, commands::Options)>
+{
+    let mut conn_params = inst.admin_conn_params()?;
+    conn_params.wait_until_available(Duration::from_secs(300));
+    let config = conn_params.build_env().await?;
+    let cli = Connection::connect(&config).await?;
+    let options = commands::Options {
+        command_line: true,
+        styler: None,
+        conn_params: Connector::new(Ok(config)),
+    };
+    Ok((cli, options))
+}
+
+/// Dumps all databases and returns the server version
+#[tokio::main]
+async fn dump(inst: &InstanceInfo, destination: &Path)
+    -> anyhow::Result<String>
+{
+    let (mut cli, options) = connect(inst).await?;
+    let version = cli.get_version().await?.to_string();
+    commands::dump_all(&mut cli, &options, destination,
+                       true /*include_secrets*/,
+                       &Default::default(), &Default::default()).await?;
+    Ok(version)
+}
+
+#[tokio::main]
+async fn restore_backup(inst: &InstanceInfo, path: &Path, with_init: bool)
+    -> anyhow::Result<()>
+{
+    let (mut cli, options) = connect(inst).await?;
+    commands::restore_all(&mut cli, &options, &Restore {
+        path: path.into(),
+        all: true,
+        verbose: false,
+        conn: None,
+        decrypt: Default::default(),
+        databases: Default::default(),
+        skip_init: !with_init,
+        only_init: false,
+        create_database: None,
+        // backup is confirmed above
+        replace: true,
+        non_interactive: true,
+        rename: Vec::new(),
+        progress: ProgressFormat::Bar,
+    }).await
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::{Duration, SystemTime};
+
+    use super::{to_prune, BackupInfo};
+
+    fn backup(id: &str, hours: u64) -> BackupInfo {
+        BackupInfo {
+            id: id.into(),
+            timestamp: SystemTime::UNIX_EPOCH
+                + Duration::from_secs(hours * 3600),
+            server_version: "3.0".into(),
+            size: 0,
+            scheduled: true,
+        }
+    }
+
+    fn ids<'a>(backups: Vec<&'a BackupInfo>) -> Vec<&'a str> {
+        backups.iter().map(|b| &b.id[..]).collect()
+    }
+
+    #[test]
+    fn retention() {
+        // newest first, two backups a day
+        let backups = vec![
+            backup("d3-2", 24*2 + 12),
+            backup("d3-1", 24*2 + 1),
+            backup("d2-2", 24 + 12),
+            backup("d2-1", 24 + 1),
+            backup("d1-2", 12),
+            backup("d1-1", 1),
+        ];
+        assert_eq!(ids(to_prune(&backups, 2, 0)),
+                   ["d2-2", "d2-1", "d1-2", "d1-1"]);
+        assert_eq!(ids(to_prune(&backups, 0, 2)),
+                   ["d3-1", "d2-1", "d1-2", "d1-1"]);
+        assert_eq!(ids(to_prune(&backups, 2, 3)),
+                   ["d2-1", "d1-1"]);
+        assert!(to_prune(&backups, 10, 0).is_empty());
+    }
+}
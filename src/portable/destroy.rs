@@ -74,7 +74,7 @@ pub fn destroy(options: &Destroy, opts: &Options) -> anyhow::Result<()> {
     Ok(())
 }
 
-fn destroy_local(name: &str) -> anyhow::Result<()> {
+fn destroy_local(name: &str, keep_backups: bool) -> anyhow::Result<()> {
     let paths = local::Paths::get(name)?;
     log::debug!("Paths {:?}", paths);
     let mut found = false;
@@ -115,6 +115,11 @@ fn destroy_local(name: &str) -> anyhow::Result<()> {
         log::info!("Removing backup directory {:?}", paths.backup_dir);
         fs::remove_dir_all(&paths.backup_dir)?;
     }
+    if paths.backups_dir.exists() && !keep_backups {
+        found = true;
+        log::info!("Removing backups {:?}", paths.backups_dir);
+        fs::remove_dir_all(&paths.backups_dir)?;
+    }
     if paths.dump_path.exists() {
         found = true;
         log::info!("Removing dump {:?}", paths.dump_path);
@@ -142,7 +147,7 @@ fn do_destroy(
             if cfg!(windows) {
                 windows::destroy(options, name)
             } else {
-                destroy_local(name)
+                destroy_local(name, options.keep_backups)
             }
         },
         InstanceName::Cloud { org_slug, name: inst_name } => {
@@ -170,6 +175,7 @@ pub fn force_by_name(name: &InstanceName, options: &Options) -> anyhow::Result<(
         force: true,
         quiet: false,
         non_interactive: true,
+        keep_backups: false,
         cloud_opts: options.cloud_options.clone(),
     }, options, name)
 }
@@ -27,11 +27,21 @@ fn socket_name(name: &str) -> String {
     format!("edgedb-server@{}.socket", name)
 }
 
+fn backup_unit_name(name: &str) -> String {
+    format!("edgedb-backup@{}.service", name)
+}
+
+fn backup_timer_name(name: &str) -> String {
+    format!("edgedb-backup@{}.timer", name)
+}
+
 pub fn service_files(name: &str) -> anyhow::Result<Vec<PathBuf>> {
     let dir = unit_dir()?;
     Ok(vec![
        dir.join(unit_name(name)),
        dir.join(socket_name(name)),
+       dir.join(backup_unit_name(name)),
+       dir.join(backup_timer_name(name)),
     ])
 }
 
@@ -121,6 +131,104 @@ WantedBy=default.target
     ))
 }
 
+#[context("cannot compose service file")]
+pub fn systemd_backup_unit(name: &str, args: &[String])
+    -> anyhow::Result<String>
+{
+    Ok(format!(r###"
+[Unit]
+Description=EdgeDB backup, instance {instance_name:?}
+Documentation=https://edgedb.com/
+After={server_unit}
+
+[Service]
+Type=oneshot
+ExecStart={executable} instance backup create -I {instance_name} --scheduled {args}
+    "###,
+        instance_name=name,
+        server_unit=unit_name(name),
+        executable=current_exe()?.display(),
+        args=args.join(" "),
+    ))
+}
+
+pub fn systemd_backup_timer(name: &str, calendar: &str) -> String {
+    format!(r###"
+[Unit]
+Description=EdgeDB backup timer, instance {instance_name:?}
+Documentation=https://edgedb.com/
+
+[Timer]
+OnCalendar={calendar}
+Persistent=true
+
+[Install]
+WantedBy=timers.target
+    "###,
+        instance_name=name,
+        calendar=calendar,
+    )
+}
+
+pub fn create_backup_timer(name: &str, calendar: &str, args: &[String])
+    -> anyhow::Result<()>
+{
+    if preliminary_detect().is_none() {
+        anyhow::bail!("either systemctl not found or environment configured incorrectly");
+    }
+    let unit_dir = unit_dir()?;
+    fs::create_dir_all(&unit_dir)
+        .with_context(|| format!("cannot create directory {:?}", unit_dir))?;
+    let unit_path = unit_dir.join(backup_unit_name(name));
+    let timer_name = backup_timer_name(name);
+    let timer_path = unit_dir.join(&timer_name);
+    fs::write(&unit_path, systemd_backup_unit(name, args)?)
+        .with_context(|| format!("cannot write {:?}", unit_path))?;
+    fs::write(&timer_path, systemd_backup_timer(name, calendar))
+        .with_context(|| format!("cannot write {:?}", timer_path))?;
+    process::Native::new("systemctl", "systemctl", "systemctl")
+        .arg("--user")
+        .arg("daemon-reload")
+        .run()?;
+    process::Native::new("timer start", "systemctl", "systemctl")
+        .arg("--user")
+        .arg("enable")
+        .arg("--now")
+        .arg(&timer_name)
+        .run()?;
+    Ok(())
+}
+
+/// Returns `false` if there was no timer installed
+pub fn remove_backup_timer(name: &str) -> anyhow::Result<bool> {
+    let unit_dir = unit_dir()?;
+    let timer_name = backup_timer_name(name);
+    let timer_path = unit_dir.join(&timer_name);
+    if !timer_path.exists() {
+        return Ok(false);
+    }
+    process::Native::new("timer stop", "systemctl", "systemctl")
+        .arg("--user")
+        .arg("disable")
+        .arg("--now")
+        .arg(&timer_name)
+        .run()?;
+    fs::remove_file(&timer_path)
+        .with_context(|| format!("cannot remove {:?}", timer_path))?;
+    let unit_path = unit_dir.join(backup_unit_name(name));
+    if unit_path.exists() {
+        fs::remove_file(&unit_path)
+            .with_context(|| format!("cannot remove {:?}", unit_path))?;
+    }
+    process::Native::new("systemctl", "systemctl", "systemctl")
+        .arg("--user")
+        .arg("daemon-reload")
+        .run()
+        .map_err(|e| log::warn!("failed to reload systemd daemon: {}", e))
+        .ok();
+    Ok(true)
+}
+
 fn systemd_is_not_found_error(e: &str) -> bool {
     e.contains("Failed to get D-Bus connection") ||
     e.contains("Failed to connect to bus") ||
@@ -197,6 +305,21 @@ pub fn stop_and_disable(name: &str) -> anyhow::Result<bool> {
         }
     }
 
+    let timer_name = backup_timer_name(name);
+    if unit_dir()?.join(&timer_name).exists() {
+        let mut cmd = process::Native::new(
+            "disable backup timer", "systemctl", "systemctl");
+        cmd.arg("--user");
+        cmd.arg("disable");
+        cmd.arg("--now");
+        cmd.arg(&timer_name);
+        if let Err((s, e)) = cmd.run_or_stderr()? {
+            log::warn!(
+                "Error running systemctl (command-line: {:?}): {}: {}",
+                cmd.command_line(), s, e);
+        }
+    }
+
     if let Some(e) = not_found_error {
         return Err(InstanceNotFound(anyhow::anyhow!(
             "no instance {:?} found: {}", name, e.trim())).into());
@@ -30,6 +30,9 @@ pub struct Paths {
     pub service_files: Vec<PathBuf>,
     pub dump_path: PathBuf,
     pub backup_dir: PathBuf,
+    /// Backups created by `instance backup`, the `backup_dir` is only
+    /// used during upgrades
+    pub backups_dir: PathBuf,
     pub upgrade_marker: PathBuf,
     pub runstate_dir: PathBuf,
 }
@@ -261,6 +264,7 @@ impl Paths {
             data_dir: base.join(name),
             dump_path: base.join(format!("{}.dump", name)),
             backup_dir: base.join(format!("{}.backup", name)),
+            backups_dir: base.join(format!("{}.backups", name)),
             upgrade_marker: base.join(format!("{}.UPGRADE_IN_PROGRESS", name)),
             runstate_dir: runstate_dir(name)?,
             service_files: if cfg!(windows) {
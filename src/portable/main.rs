@@ -2,6 +2,7 @@ use crate::options::Options;
 use crate::portable::project::ProjectCommand;
 use crate::portable::options::{ServerCommand, ServerInstanceCommand};
 
+use crate::portable::backup;
 use crate::portable::control;
 use crate::portable::create;
 use crate::portable::credentials;
@@ -58,6 +59,7 @@ pub fn instance_main(cmd: &ServerInstanceCommand, options: &Options)
         Status(c) if cfg!(windows) => windows::status(c),
         Status(c) => status::status(c, options),
         Credentials(c) => credentials::show_credentials(&options, &c),
+        Backup(c) => backup::backup(c),
     }
 }
 
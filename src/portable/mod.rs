@@ -11,6 +11,7 @@ pub mod macos;
 pub mod linux;
 pub mod windows;
 
+mod backup;
 mod control;
 mod create;
 mod credentials;
@@ -19,7 +20,7 @@ mod info;
 pub mod install;
 mod link;
 mod list_versions;
-mod reset_password;
+pub mod reset_password;
 mod revert;
 pub mod status;
 mod uninstall;
@@ -62,6 +62,8 @@ pub enum InstanceCommand {
     ResetPassword(ResetPassword),
     /// Display instance credentials (add `--json` for verbose)
     Credentials(ShowCredentials),
+    /// Create, list, restore and prune local backups of an instance
+    Backup(Backup),
 }
 
 #[derive(clap::Subcommand, Clone, Debug)]
@@ -241,6 +243,11 @@ pub struct Destroy {
     /// Do not ask questions, assume user wants to delete instance
     #[arg(long)]
     pub non_interactive: bool,
+
+    /// Keep backups made by `edgedb instance backup`, by default they are
+    /// deleted with the instance
+    #[arg(long)]
+    pub keep_backups: bool,
 }
 
 #[derive(clap::Args, Clone, Debug)]
@@ -613,6 +620,108 @@ pub struct ShowCredentials {
     pub insecure_dsn: bool,
 }
 
+#[derive(clap::Args, Debug, Clone)]
+pub struct Backup {
+    #[command(subcommand)]
+    pub subcommand: BackupCommand,
+}
+
+#[derive(clap::Subcommand, Clone, Debug)]
+pub enum BackupCommand {
+    /// Dump all databases of the instance into a new backup
+    Create(BackupCreate),
+    /// Show backups of the instance
+    List(BackupList),
+    /// Restore databases of the instance from a backup
+    Restore(BackupRestore),
+    /// Remove old backups according to the retention options
+    Prune(BackupPrune),
+}
+
+#[derive(clap::Args, Debug, Clone)]
+pub struct BackupRetention {
+    /// Keep the specified number of most recent backups
+    #[arg(long, value_name="N")]
+    #[arg(value_parser=clap::value_parser!(u32).range(1..))]
+    pub keep_last: Option<u32>,
+
+    /// Keep the most recent backup of each of the specified number of
+    /// most recent days (in UTC) that have backups
+    #[arg(long, value_name="N")]
+    #[arg(value_parser=clap::value_parser!(u32).range(1..))]
+    pub keep_daily: Option<u32>,
+}
+
+#[derive(clap::Args, Debug, Clone)]
+pub struct BackupCreate {
+    /// Instance to back up
+    #[arg(short='I', long)]
+    #[arg(value_hint=ValueHint::Other)]  // TODO complete instance name
+    pub instance: InstanceName,
+
+    // old backups are pruned after the new one is created
+    #[command(flatten)]
+    pub retention: BackupRetention,
+
+    /// Instead of creating a backup now, install a systemd timer that
+    /// creates backups on the specified calendar event (e.g. `daily` or
+    /// `*-*-* 03:00:00`), retention options are applied on each run
+    #[arg(long, value_name="calendar", conflicts_with="unschedule")]
+    pub schedule: Option<String>,
+
+    /// Remove the timer installed by `--schedule`
+    #[arg(long)]
+    pub unschedule: bool,
+
+    /// Backup is created by the timer
+    #[arg(long, hide=true)]
+    pub scheduled: bool,
+}
+
+#[derive(clap::Args, Debug, Clone)]
+pub struct BackupList {
+    #[arg(short='I', long)]
+    #[arg(value_hint=ValueHint::Other)]  // TODO complete instance name
+    pub instance: InstanceName,
+
+    /// Output in JSON format
+    #[arg(long)]
+    pub json: bool,
+}
+
+#[derive(clap::Args, Debug, Clone)]
+pub struct BackupRestore {
+    #[arg(short='I', long)]
+    #[arg(value_hint=ValueHint::Other)]  // TODO complete instance name
+    pub instance: InstanceName,
+
+    /// Backup to restore (as shown by `backup list`), latest by default
+    pub backup_id: Option<String>,
+
+    /// Also apply server configuration and roles from the backup, this
+    /// fails if the roles already exist in the instance
+    #[arg(long)]
+    pub with_init: bool,
+
+    /// Do not ask for confirmation
+    #[arg(long)]
+    pub non_interactive: bool,
+}
+
+#[derive(clap::Args, Debug, Clone)]
+pub struct BackupPrune {
+    #[arg(short='I', long)]
+    #[arg(value_hint=ValueHint::Other)]  // TODO complete instance name
+    pub instance: InstanceName,
+
+    #[command(flatten)]
+    pub retention: BackupRetention,
+
+    /// Only show backups that would be removed
+    #[arg(long)]
+    pub dry_run: bool,
+}
+
 impl FromStr for StartConf {
     type Err = anyhow::Error;
     fn from_str(s: &str) -> anyhow::Result<StartConf> {
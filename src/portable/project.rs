@@ -1490,6 +1490,34 @@ fn search_for_unlink(base: &Path) -> anyhow::Result<PathBuf> {
     anyhow::bail!("no project directory found");
 }
 
+/// Returns name of the database that the project in the current directory
+/// is linked to, if the project is linked to the specified instance
+pub fn linked_database(instance: &edgedb_tokio::InstanceName)
+    -> anyhow::Result<Option<String>>
+{
+    let Some(project_dir) = project_dir_opt(None)? else {
+        return Ok(None);
+    };
+    let stash_dir = stash_path(&project_dir)?;
+    if !stash_dir.exists() {
+        return Ok(None);
+    }
+    let same = match (instance_name(&stash_dir)?, instance) {
+        (InstanceName::Local(a), edgedb_tokio::InstanceName::Local(b)) => {
+            &a == b
+        }
+        (
+            InstanceName::Cloud { org_slug: a_org, name: a_name },
+            edgedb_tokio::InstanceName::Cloud { org_slug: b_org, name: b_name },
+        ) => &a_org == b_org && &a_name == b_name,
+        _ => false,
+    };
+    if !same {
+        return Ok(None);
+    }
+    Ok(Some(database_name(&stash_dir)?.unwrap_or_else(|| "edgedb".into())))
+}
+
 #[context("cannot read instance name of {:?}", stash_dir)]
 fn instance_name(stash_dir: &Path) -> anyhow::Result<InstanceName> {
     let inst = fs::read_to_string(&stash_dir.join("instance-name"))?;
@@ -32,6 +32,21 @@ pub fn generate_password() -> String {
     }).collect()
 }
 
+/// Asks for a new password on the terminal twice until both match
+pub fn ask_new_password(user: &str) -> anyhow::Result<String> {
+    loop {
+        let password = tty_password::read(
+            format!("New password for '{}': ", user.escape_default()))?;
+        let confirm = tty_password::read(
+            format!("Confirm password for '{}': ", user.escape_default()))?;
+        if password != confirm {
+            print::error("Passwords do not match");
+        } else {
+            return Ok(password);
+        }
+    }
+}
+
 #[context("error reading credentials at {}", path.display())]
 fn read_credentials(path: &Path) -> anyhow::Result<Credentials> {
     let data = fs::read(&path)?;
@@ -70,17 +85,7 @@ pub fn reset_password(options: &ResetPassword) -> anyhow::Result<()> {
     let password = if options.password_from_stdin {
         tty_password::read_stdin()?
     } else if options.password {
-        loop {
-            let password = tty_password::read(
-                format!("New password for '{}': ", user.escape_default()))?;
-            let confirm = tty_password::read(
-                format!("Confirm password for '{}': ", user.escape_default()))?;
-            if password != confirm {
-                print::error("Passwords do not match");
-            } else {
-                break password;
-            }
-        }
+        ask_new_password(&user)?
     } else {
         generate_password()
     };
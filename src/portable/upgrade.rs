@@ -376,7 +376,8 @@ pub async fn dump_instance(inst: &InstanceInfo, destination: &Path)
         conn_params: Connector::new(Ok(config)),
     };
     commands::dump_all(&mut cli, &options, destination.as_ref(),
-                       true /*include_secrets*/).await?;
+                       true /*include_secrets*/,
+                       &Default::default(), &Default::default()).await?;
     Ok(())
 }
 
@@ -439,7 +440,7 @@ fn reinit_and_restore(inst: &InstanceInfo, paths: &Paths) -> anyhow::Result<()>
 async fn restore_instance(inst: &InstanceInfo, path: &Path)
     -> anyhow::Result<()>
 {
-    use crate::commands::parser::Restore;
+    use crate::commands::parser::{ProgressFormat, Restore};
     let mut conn_params = inst.admin_conn_params()?;
     conn_params.wait_until_available(Duration::from_secs(300));
 
@@ -457,6 +458,15 @@ async fn restore_instance(inst: &InstanceInfo, path: &Path)
         all: true,
         verbose: false,
         conn: None,
+        decrypt: Default::default(),
+        databases: Default::default(),
+        skip_init: false,
+        only_init: false,
+        create_database: None,
+        replace: false,
+        non_interactive: false,
+        rename: Vec::new(),
+        progress: ProgressFormat::Bar,
     }).await?;
     Ok(())
 }
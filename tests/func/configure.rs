@@ -111,3 +111,224 @@ fn configure_all_parameters() {
         assert_eq!(db_reset_options, cmd_reset_options); // nice diff
     }
 }
+
+#[test]
+fn configure_database_scope() {
+    SERVER.admin_cmd()
+        .arg("configure").arg("set")
+        .arg("--scope=database")
+        .arg("query_execution_timeout").arg("10 seconds")
+        .assert().success();
+    SERVER.admin_cmd()
+        .arg("query")
+        .arg("SELECT <str>cfg::Config.query_execution_timeout")
+        .assert().success()
+        .stdout("\"PT10S\"\n");
+    SERVER.admin_cmd()
+        .arg("configure").arg("reset")
+        .arg("--scope=database")
+        .arg("query_execution_timeout")
+        .assert().success();
+}
+
+#[test]
+fn configure_show() {
+    SERVER.admin_cmd()
+        .arg("configure").arg("set")
+        .arg("--scope=database")
+        .arg("query_execution_timeout").arg("7 seconds")
+        .assert().success();
+    SERVER.admin_cmd()
+        .arg("configure").arg("show")
+        .arg("query_execution_timeout")
+        .arg("--json")
+        .assert().success()
+        .stdout(predicates::str::contains(r#""source": "database""#));
+    SERVER.admin_cmd()
+        .arg("configure").arg("reset")
+        .arg("--scope=database")
+        .arg("query_execution_timeout")
+        .assert().success();
+
+    SERVER.admin_cmd()
+        .arg("configure").arg("set")
+        .arg("no_such_setting").arg("1")
+        .assert().failure()
+        .stderr(predicates::str::contains("unknown configuration setting"));
+}
+
+#[test]
+fn configure_export_apply() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("config.toml");
+    std::fs::write(&path, r#"
+        [database]
+        query_execution_timeout = "17 seconds"
+    "#).unwrap();
+    SERVER.admin_cmd()
+        .arg("configure").arg("apply").arg(&path).arg("--dry-run")
+        .assert().success()
+        .stdout(predicates::str::contains(
+            "CONFIGURE CURRENT DATABASE SET query_execution_timeout"));
+    SERVER.admin_cmd()
+        .arg("configure").arg("apply").arg(&path)
+        .assert().success();
+    let cmd = SERVER.admin_cmd()
+        .arg("configure").arg("export")
+        .assert().success();
+    let out = String::from_utf8(cmd.get_output().stdout.clone()).unwrap();
+    assert!(out.contains("query_execution_timeout = \"PT17S\""), "{}", out);
+
+    // applying exported config is a no-op
+    std::fs::write(&path, out).unwrap();
+    SERVER.admin_cmd()
+        .arg("configure").arg("apply").arg(&path).arg("--dry-run")
+        .assert().success()
+        .stdout("");
+    SERVER.admin_cmd()
+        .arg("configure").arg("reset")
+        .arg("--scope=database")
+        .arg("query_execution_timeout")
+        .assert().success();
+}
+
+#[test]
+fn configure_apply_inherited() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("config.toml");
+    // the default value, inherited by the database
+    std::fs::write(&path, r#"
+        [database]
+        session_idle_transaction_timeout = "10 seconds"
+    "#).unwrap();
+    for _ in 0..2 {
+        SERVER.admin_cmd()
+            .arg("configure").arg("apply").arg(&path)
+            .assert().success()
+            .stdout("")
+            .stderr(predicates::str::contains(
+                "Configuration is up to date"));
+    }
+}
+
+#[test]
+fn configure_apply_instance_auth() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("config.toml");
+    SERVER.admin_cmd()
+        .arg("configure").arg("insert").arg("auth")
+        .arg("--priority=118").arg("--user=apply_test_user")
+        .arg("--method=scram")
+        .assert().success();
+    SERVER.admin_cmd()
+        .arg("configure").arg("set")
+        .arg("query_execution_timeout").arg("18 seconds")
+        .assert().success();
+    let cmd = SERVER.admin_cmd()
+        .arg("configure").arg("export")
+        .assert().success();
+    let out = String::from_utf8(cmd.get_output().stdout.clone()).unwrap();
+    assert!(out.contains("[[instance.Auth]]"), "{}", out);
+    std::fs::write(&path, &out).unwrap();
+    SERVER.admin_cmd()
+        .arg("configure").arg("auth").arg("remove")
+        .arg("--priority=118").arg("--non-interactive")
+        .assert().success();
+    SERVER.admin_cmd()
+        .arg("configure").arg("reset")
+        .arg("query_execution_timeout")
+        .assert().success();
+
+    // only the missing rule and setting are restored
+    let cmd = SERVER.admin_cmd()
+        .arg("configure").arg("apply").arg(&path).arg("--dry-run")
+        .assert().success();
+    let plan = String::from_utf8(cmd.get_output().stdout.clone()).unwrap();
+    assert!(plan.contains("CONFIGURE INSTANCE INSERT Auth"), "{}", plan);
+    assert!(plan.contains("CONFIGURE INSTANCE SET query_execution_timeout"),
+            "{}", plan);
+    assert!(!plan.contains("RESET"), "{}", plan);
+    SERVER.admin_cmd()
+        .arg("configure").arg("apply").arg(&path)
+        .assert().success();
+    SERVER.admin_cmd()
+        .arg("configure").arg("auth").arg("list").arg("--json")
+        .assert().success()
+        .stdout(predicates::str::contains("apply_test_user"));
+
+    // method names are matched case-insensitively
+    std::fs::write(&path, out.replace("\"SCRAM\"", "\"scram\"")).unwrap();
+    SERVER.admin_cmd()
+        .arg("configure").arg("apply").arg(&path).arg("--dry-run")
+        .assert().success()
+        .stdout("");
+
+    // invalid rules are rejected before anything is changed
+    std::fs::write(&path, format!("{}\n[[instance.Auth]]\n\
+                                   priority = 119\n\
+                                   method = \"NoSuchMethod\"\n", out))
+        .unwrap();
+    SERVER.admin_cmd()
+        .arg("configure").arg("apply").arg(&path)
+        .assert().failure()
+        .stderr(predicates::str::contains("unknown authentication method"));
+    SERVER.admin_cmd()
+        .arg("configure").arg("auth").arg("list").arg("--json")
+        .assert().success()
+        .stdout(predicates::str::contains("apply_test_user"));
+
+    // a file without the instance section leaves instance config intact
+    std::fs::write(&path, r#"
+        [database]
+    "#).unwrap();
+    SERVER.admin_cmd()
+        .arg("configure").arg("apply").arg(&path).arg("--dry-run")
+        .assert().success()
+        .stdout("");
+
+    SERVER.admin_cmd()
+        .arg("configure").arg("auth").arg("remove")
+        .arg("--priority=118").arg("--non-interactive")
+        .assert().success();
+    SERVER.admin_cmd()
+        .arg("configure").arg("reset")
+        .arg("query_execution_timeout")
+        .assert().success();
+}
+
+#[test]
+fn configure_auth() {
+    SERVER.admin_cmd()
+        .arg("configure").arg("insert").arg("auth")
+        .arg("--priority=117").arg("--user=auth_test_user")
+        .arg("--method=scram")
+        .assert().success();
+    SERVER.admin_cmd()
+        .arg("configure").arg("auth").arg("edit")
+        .arg("--priority=117").arg("--comment=edited")
+        .assert().success();
+    SERVER.admin_cmd()
+        .arg("configure").arg("auth").arg("list").arg("--json")
+        .assert().success()
+        .stdout(predicates::str::contains(r#""comment": "edited""#));
+    SERVER.admin_cmd()
+        .arg("configure").arg("auth").arg("edit")
+        .arg("--priority=117").arg("--method=no_such_method")
+        .assert().failure()
+        .stderr(predicates::str::contains("unknown authentication method"));
+    SERVER.admin_cmd()
+        .arg("configure").arg("insert").arg("auth")
+        .arg("--scope=database")
+        .arg("--priority=119").arg("--method=scram")
+        .assert().failure()
+        .stderr(predicates::str::contains(
+            "can only be configured for the instance"));
+    SERVER.admin_cmd()
+        .arg("configure").arg("auth").arg("remove")
+        .arg("--priority=117").arg("--non-interactive")
+        .assert().success();
+    SERVER.admin_cmd()
+        .arg("configure").arg("auth").arg("remove")
+        .arg("--priority=117").arg("--non-interactive")
+        .assert().failure();
+}
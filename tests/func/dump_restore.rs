@@ -63,3 +63,388 @@ fn dump_restore_all() {
         .stdout("\"world\"\n");
     println!("query");
 }
+
+#[test]
+fn dump_verify_inspect() {
+    std::fs::create_dir_all("./tmp").expect("can create directory");
+    SERVER.admin_cmd().arg("database").arg("create").arg("dump_03")
+        .assert().success();
+    SERVER.database_cmd("dump_03").arg("query")
+        .arg("CREATE TYPE Hello { CREATE REQUIRED PROPERTY name -> str; }")
+        .arg("INSERT Hello { name := 'world' }")
+        .assert().success();
+    SERVER.database_cmd("dump_03").arg("dump").arg("./tmp/dump_03.dump")
+        .assert().success();
+
+    SERVER.admin_cmd().arg("dump").arg("verify").arg("./tmp/dump_03.dump")
+        .assert().success();
+    let cmd = SERVER.admin_cmd()
+        .arg("dump").arg("inspect").arg("--json").arg("./tmp/dump_03.dump")
+        .assert().success();
+    let out: serde_json::Value = serde_json::from_slice(
+        &cmd.get_output().stdout).unwrap();
+    assert_eq!(out["format_version"], 1);
+    assert!(out["server_version"].is_string());
+
+    let mut data = std::fs::read("./tmp/dump_03.dump").unwrap();
+    std::fs::write("./tmp/dump_03_truncated.dump", &data[..data.len() - 1])
+        .unwrap();
+    SERVER.admin_cmd()
+        .arg("dump").arg("verify").arg("./tmp/dump_03_truncated.dump")
+        .assert().failure();
+    let last = data.len() - 1;
+    data[last] ^= 0xFF;
+    std::fs::write("./tmp/dump_03_corrupted.dump", &data).unwrap();
+    SERVER.admin_cmd()
+        .arg("dump").arg("verify").arg("./tmp/dump_03_corrupted.dump")
+        .assert().failure()
+        .stderr(predicates::str::contains("Checksum mismatch"));
+
+    SERVER.admin_cmd().arg("database").arg("create").arg("restore_03")
+        .assert().success();
+    SERVER.database_cmd("restore_03")
+        .arg("restore").arg("./tmp/dump_03_corrupted.dump")
+        .assert().failure();
+}
+
+#[test]
+fn dump_restore_compressed() {
+    std::fs::create_dir_all("./tmp").expect("can create directory");
+    SERVER.admin_cmd().arg("database").arg("create").arg("dump_04")
+        .assert().success();
+    SERVER.database_cmd("dump_04").arg("query")
+        .arg("CREATE TYPE Hello { CREATE REQUIRED PROPERTY name -> str; }")
+        .arg("INSERT Hello { name := 'world' }")
+        .assert().success();
+    for (compress, name) in [("zstd:19", "zstd"), ("gzip", "gzip")] {
+        let path = format!("./tmp/dump_04.{}.dump", name);
+        SERVER.database_cmd("dump_04")
+            .arg("dump").arg("--compress").arg(compress).arg(&path)
+            .assert().success();
+        SERVER.admin_cmd()
+            .arg("dump").arg("inspect").arg("--json").arg(&path)
+            .assert().success()
+            .stdout(predicates::str::contains(
+                format!(r#""compression": "{}""#, name)));
+
+        let database = format!("restore_04_{}", name);
+        SERVER.admin_cmd().arg("database").arg("create").arg(&database)
+            .assert().success();
+        SERVER.database_cmd(&database).arg("restore").arg(&path)
+            .assert().success();
+        SERVER.database_cmd(&database).arg("query")
+            .arg("SELECT Hello.name")
+            .assert().success()
+            .stdout("\"world\"\n");
+    }
+    SERVER.admin_cmd().arg("dump").arg("--compress").arg("lz4").arg("x.dump")
+        .assert().failure();
+}
+
+#[test]
+fn dump_restore_encrypted() {
+    std::fs::create_dir_all("./tmp").expect("can create directory");
+    std::fs::write("./tmp/dump_05.passphrase", "correct horse\n")
+        .expect("can write passphrase");
+    std::fs::write("./tmp/dump_05.wrong", "battery staple\n")
+        .expect("can write passphrase");
+    SERVER.admin_cmd().arg("database").arg("create").arg("dump_05")
+        .assert().success();
+    SERVER.database_cmd("dump_05").arg("query")
+        .arg("CREATE TYPE Hello { CREATE REQUIRED PROPERTY name -> str; }")
+        .arg("INSERT Hello { name := 'world' }")
+        .assert().success();
+    SERVER.database_cmd("dump_05")
+        .arg("dump").arg("--compress").arg("zstd")
+        .arg("--passphrase-file=./tmp/dump_05.passphrase")
+        .arg("./tmp/dump_05.dump")
+        .assert().success();
+    SERVER.admin_cmd()
+        .arg("dump").arg("verify").arg("./tmp/dump_05.dump")
+        .assert().failure()
+        .stderr(predicates::str::contains("--passphrase-file"));
+    SERVER.admin_cmd()
+        .arg("dump").arg("verify")
+        .arg("--passphrase-file=./tmp/dump_05.wrong")
+        .arg("./tmp/dump_05.dump")
+        .assert().failure();
+    SERVER.admin_cmd()
+        .arg("dump").arg("inspect").arg("--json")
+        .arg("--passphrase-file=./tmp/dump_05.passphrase")
+        .arg("./tmp/dump_05.dump")
+        .assert().success()
+        .stdout(predicates::str::contains(r#""encryption": "age-scrypt""#))
+        .stdout(predicates::str::contains(r#""compression": "zstd""#));
+
+    SERVER.admin_cmd().arg("database").arg("create").arg("restore_05")
+        .assert().success();
+    SERVER.database_cmd("restore_05").arg("restore")
+        .arg("--passphrase-file=./tmp/dump_05.passphrase")
+        .arg("./tmp/dump_05.dump")
+        .assert().success();
+    SERVER.database_cmd("restore_05").arg("query")
+        .arg("SELECT Hello.name")
+        .assert().success()
+        .stdout("\"world\"\n");
+}
+
+#[test]
+fn dump_restore_all_selective() {
+    for database in ["dump_06_a", "dump_06_b", "dump_06_skip"] {
+        SERVER.admin_cmd().arg("database").arg("create").arg(database)
+            .assert().success();
+        SERVER.database_cmd(database).arg("query")
+            .arg("CREATE TYPE Hello { CREATE REQUIRED PROPERTY name -> str; }")
+            .arg("INSERT Hello { name := 'world' }")
+            .assert().success();
+    }
+    SERVER.admin_cmd().arg("dump")
+        .arg("--all").arg("--format=dir").arg("--jobs=2")
+        .arg("--include-db=dump_06_*").arg("--exclude-db=*_skip")
+        .arg("./tmp/dump_06")
+        .assert().success();
+    assert!(std::path::Path::new("./tmp/dump_06/dump_06_a.dump").exists());
+    assert!(std::path::Path::new("./tmp/dump_06/dump_06_b.dump").exists());
+    assert!(!std::path::Path::new("./tmp/dump_06/dump_06_skip.dump").exists());
+    assert!(!std::path::Path::new("./tmp/dump_06/edgedb.dump").exists());
+
+    let new_instance = ServerGuard::start();
+    new_instance.admin_cmd().arg("restore").arg("--all")
+        .arg("--skip-init").arg("--only-init").arg("./tmp/dump_06")
+        .assert().failure();
+    new_instance.admin_cmd().arg("restore").arg("--all")
+        .arg("--jobs=2").arg("--skip-init").arg("--exclude-db=*_b")
+        .arg("./tmp/dump_06")
+        .assert().success();
+    new_instance.database_cmd("dump_06_a").arg("query")
+        .arg("SELECT Hello.name")
+        .assert().success()
+        .stdout("\"world\"\n");
+    new_instance.admin_cmd().arg("query")
+        .arg("SELECT count((SELECT sys::Database FILTER .name = 'dump_06_b'))")
+        .assert().success()
+        .stdout("0\n");
+}
+
+#[test]
+fn restore_create_replace_rename() {
+    SERVER.admin_cmd().arg("database").arg("create").arg("dump_07")
+        .assert().success();
+    SERVER.database_cmd("dump_07").arg("query")
+        .arg("CREATE TYPE Hello { CREATE REQUIRED PROPERTY name -> str; }")
+        .arg("INSERT Hello { name := 'world' }")
+        .assert().success();
+    SERVER.database_cmd("dump_07").arg("dump").arg("./tmp/dump_07.dump")
+        .assert().success();
+
+    SERVER.admin_cmd().arg("restore")
+        .arg("--create-database=restore_07").arg("./tmp/dump_07.dump")
+        .assert().success();
+    SERVER.admin_cmd().arg("restore")
+        .arg("--create-database=restore_07").arg("./tmp/dump_07.dump")
+        .assert().failure()
+        .stderr(predicates::str::contains("--replace"));
+    SERVER.admin_cmd().arg("restore")
+        .arg("--create-database=restore_07")
+        .arg("--replace").arg("--non-interactive")
+        .arg("./tmp/dump_07.dump")
+        .assert().success();
+    SERVER.database_cmd("restore_07").arg("query")
+        .arg("SELECT Hello.name")
+        .assert().success()
+        .stdout("\"world\"\n");
+
+    SERVER.admin_cmd().arg("dump")
+        .arg("--all").arg("--format=dir").arg("--include-db=dump_07")
+        .arg("./tmp/dump_07")
+        .assert().success();
+    SERVER.admin_cmd().arg("restore").arg("--all").arg("--skip-init")
+        .arg("--rename=missing=restore_07_x")
+        .arg("./tmp/dump_07")
+        .assert().failure();
+    SERVER.admin_cmd().arg("restore").arg("--all").arg("--skip-init")
+        .arg("--rename=dump_07=restore_07_all")
+        .arg("./tmp/dump_07")
+        .assert().success();
+    SERVER.database_cmd("restore_07_all").arg("query")
+        .arg("SELECT Hello.name")
+        .assert().success()
+        .stdout("\"world\"\n");
+}
+
+#[test]
+fn restore_progress_json() {
+    SERVER.admin_cmd().arg("database").arg("create").arg("dump_08")
+        .assert().success();
+    SERVER.database_cmd("dump_08").arg("query")
+        .arg("CREATE TYPE Hello { CREATE REQUIRED PROPERTY name -> str; }")
+        .arg("INSERT Hello { name := 'world' }")
+        .assert().success();
+    SERVER.database_cmd("dump_08").arg("dump").arg("./tmp/dump_08.dump")
+        .assert().success();
+    SERVER.admin_cmd().arg("restore").arg("--progress=json")
+        .arg("--create-database=restore_08").arg("./tmp/dump_08.dump")
+        .assert().success()
+        .stdout(predicates::str::contains(r#""database":"restore_08""#))
+        .stdout(predicates::str::contains(r#""percent":100.0"#))
+        .stdout(predicates::str::contains(r#""done":true"#));
+}
+
+#[test]
+fn dump_extract_schema() {
+    SERVER.admin_cmd().arg("database").arg("create").arg("dump_09")
+        .assert().success();
+    SERVER.database_cmd("dump_09").arg("query")
+        .arg("CREATE TYPE Hello { CREATE REQUIRED PROPERTY name -> str; }")
+        .arg("INSERT Hello { name := 'world' }")
+        .assert().success();
+    SERVER.database_cmd("dump_09").arg("dump").arg("./tmp/dump_09.dump")
+        .assert().success();
+
+    SERVER.admin_cmd()
+        .arg("dump").arg("extract-schema").arg("./tmp/dump_09.dump")
+        .assert().success()
+        .stdout(predicates::str::contains("CREATE TYPE default::Hello"));
+    SERVER.admin_cmd()
+        .arg("dump").arg("extract-schema").arg("--sdl")
+        .arg("./tmp/dump_09.dump")
+        .assert().success()
+        .stdout(predicates::str::contains("type Hello"));
+    SERVER.admin_cmd()
+        .arg("dump").arg("extract-schema").arg("--sdl")
+        .arg("--out=./tmp/schema_09").arg("./tmp/dump_09.dump")
+        .assert().success();
+    let sdl = std::fs::read_to_string("./tmp/schema_09/default.esdl")
+        .expect("schema is written");
+    assert!(sdl.contains("type Hello"), "{}", sdl);
+    SERVER.admin_cmd().arg("query")
+        .arg("SELECT count((SELECT sys::Database \
+              FILTER .name LIKE 'extract_schema_%'))")
+        .assert().success()
+        .stdout("0\n");
+}
+
+#[test]
+fn export_import_jsonl() {
+    let schema = [
+        "CREATE TYPE Person { \
+            CREATE REQUIRED PROPERTY name -> str; \
+            CREATE MULTI PROPERTY tags -> str; \
+            CREATE LINK best_friend -> Person; \
+        }",
+        "CREATE TYPE Post { \
+            CREATE REQUIRED LINK author -> Person; \
+            CREATE MULTI LINK readers -> Person { \
+                CREATE PROPERTY rating -> int64; \
+            }; \
+        }",
+    ];
+    SERVER.admin_cmd().arg("database").arg("create").arg("export_01")
+        .assert().success();
+    SERVER.database_cmd("export_01").arg("query")
+        .args(schema)
+        .arg("INSERT Person { name := 'alice', tags := {'a', 'b'} }")
+        .arg("INSERT Person { name := 'bob', \
+              best_friend := (SELECT Person FILTER .name = 'alice') }")
+        .arg("UPDATE Person FILTER .name = 'alice' SET { \
+              best_friend := (SELECT DETACHED Person FILTER .name = 'bob') }")
+        .arg("INSERT Post { \
+              author := (SELECT Person FILTER .name = 'alice'), \
+              readers := (SELECT Person { @rating := 5 } \
+                          FILTER .name = 'bob') }")
+        .assert().success();
+
+    SERVER.database_cmd("export_01").arg("export")
+        .arg("--dir=./tmp/export_01")
+        .assert().success();
+    let people = std::fs::read_to_string("./tmp/export_01/default.Person.jsonl")
+        .expect("data file is written");
+    assert_eq!(people.lines().count(), 2, "{}", people);
+    SERVER.database_cmd("export_01").arg("export")
+        .arg("--dir=./tmp/export_01")
+        .assert().code(1);
+    // resuming a complete export changes nothing
+    SERVER.database_cmd("export_01").arg("export").arg("--resume")
+        .arg("--dir=./tmp/export_01")
+        .assert().success();
+
+    SERVER.admin_cmd().arg("database").arg("create").arg("import_01")
+        .assert().success();
+    SERVER.database_cmd("import_01").arg("query")
+        .args(schema)
+        .assert().success();
+    SERVER.database_cmd("import_01").arg("import")
+        .arg("--dir=./tmp/export_01")
+        .assert().success();
+    SERVER.database_cmd("import_01").arg("query")
+        .arg("SELECT Post.author.name")
+        .arg("SELECT Post.readers@rating")
+        .arg("SELECT (SELECT Person FILTER .name = 'alice').best_friend.name")
+        .assert().success()
+        .stdout("\"alice\"\n5\n\"bob\"\n");
+    let id_query = "SELECT <str>(SELECT Post LIMIT 1).id";
+    let exported = SERVER.database_cmd("export_01").arg("query")
+        .arg(id_query).output().expect("query runs");
+    SERVER.database_cmd("import_01").arg("query").arg(id_query)
+        .assert().success()
+        .stdout(String::from_utf8(exported.stdout).unwrap());
+}
+
+#[test]
+fn load_csv_jsonl() {
+    SERVER.admin_cmd().arg("database").arg("create").arg("load_01")
+        .assert().success();
+    SERVER.database_cmd("load_01").arg("query")
+        .arg("CREATE TYPE Person { \
+                CREATE REQUIRED PROPERTY name -> str { \
+                    CREATE CONSTRAINT exclusive; \
+                }; \
+              }")
+        .arg("CREATE TYPE Movie { \
+                CREATE REQUIRED PROPERTY title -> str { \
+                    CREATE CONSTRAINT exclusive; \
+                }; \
+                CREATE PROPERTY year -> int64; \
+                CREATE LINK director -> Person; \
+              }")
+        .arg("INSERT Person { name := 'Nolan' }")
+        .assert().success();
+
+    std::fs::create_dir_all("./tmp").expect("can create directory");
+    std::fs::write("./tmp/load_01.csv",
+        "title,released,director\n\
+         Inception,2010,Nolan\n\
+         Memento,2000,Nolan\n\
+         Inception,2010,Nolan\n\
+         Dunkirk,2017\n\
+         Tenet,2020,Unknown\n").expect("can write file");
+    SERVER.database_cmd("load_01").arg("load")
+        .arg("--type=Movie").arg("--file=./tmp/load_01.csv")
+        .arg("--map=title=title").arg("--map=released=year")
+        .arg("--link=director=director.name")
+        .arg("--batch-size=2").arg("--on-error=skip")
+        .arg("--rejected=./tmp/load_01_rejected.jsonl")
+        .assert().success();
+    SERVER.database_cmd("load_01").arg("query")
+        .arg("SELECT count(Movie)")
+        .arg("SELECT (SELECT Movie FILTER .title = 'Memento').director.name")
+        .assert().success()
+        .stdout("2\n\"Nolan\"\n");
+    let rejected = std::fs::read_to_string("./tmp/load_01_rejected.jsonl")
+        .expect("rejected rows are written");
+    assert_eq!(rejected.lines().count(), 2, "{}", rejected);
+    assert!(rejected.contains("expected 3 fields, found 2"), "{}", rejected);
+    assert!(rejected.contains("Tenet"), "{}", rejected);
+
+    std::fs::write("./tmp/load_01.jsonl",
+        "{\"title\": \"Heat\", \"year\": 1995}\n\
+         {\"title\": \"Alien\", \"year\": \"unknown\"}\n")
+        .expect("can write file");
+    SERVER.database_cmd("load_01").arg("load")
+        .arg("--type=default::Movie").arg("--file=./tmp/load_01.jsonl")
+        .assert().code(1);
+    SERVER.database_cmd("load_01").arg("query")
+        .arg("SELECT count(Movie)")
+        .assert().success()
+        .stdout("3\n");
+}
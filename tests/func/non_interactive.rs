@@ -44,6 +44,96 @@ fn list_indexes() {
         .assert().success();
 }
 
+#[test]
+fn list_json() {
+    SERVER.admin_cmd()
+        .arg("list")
+        .arg("modules")
+        .arg("--json")
+        .arg("^default$")
+        .assert().success()
+        .stdout(predicates::str::contains(r#""name": "default""#));
+}
+
+#[test]
+fn list_databases_verbose() {
+    let cmd = SERVER.admin_cmd()
+        .arg("list")
+        .arg("databases")
+        .arg("--json")
+        .arg("--count-objects")
+        .assert().success();
+    let out: serde_json::Value = serde_json::from_slice(
+        &cmd.get_output().stdout).unwrap();
+    let db = out.as_array().unwrap().iter()
+        .find(|db| db["name"] == "edgedb")
+        .expect("default database is listed");
+    assert!(db["objects"].is_i64());
+    assert!(db["object_types"].is_i64());
+}
+
+#[test]
+fn list_functions() {
+    SERVER.admin_cmd()
+        .arg("list")
+        .arg("functions")
+        .arg("--system")
+        .arg("^std::len$")
+        .assert().success()
+        .stdout(predicates::str::contains("std::len\t(str: std::str)"));
+}
+
+#[test]
+fn list_globals() {
+    SERVER.admin_cmd()
+        .arg("database").arg("create").arg("test_list_globals")
+        .assert().success();
+    SERVER.admin_cmd()
+        .arg("query")
+        .arg("--database=test_list_globals")
+        .arg("CREATE GLOBAL test_flag -> bool")
+        .assert().success();
+    SERVER.admin_cmd()
+        .arg("--database=test_list_globals")
+        .arg("list")
+        .arg("globals")
+        .arg("--json")
+        .assert().success()
+        .stdout(predicates::str::contains(
+            r#""name": "default::test_flag""#));
+}
+
+#[test]
+fn list_extensions() {
+    SERVER.admin_cmd()
+        .arg("list")
+        .arg("extensions")
+        .arg("--json")
+        .assert().success()
+        .stdout(predicates::str::starts_with("["));
+}
+
+#[test]
+fn list_policies() {
+    SERVER.admin_cmd()
+        .arg("database").arg("create").arg("test_list_policies")
+        .assert().success();
+    SERVER.admin_cmd()
+        .arg("query")
+        .arg("--database=test_list_policies")
+        .arg("CREATE TYPE Doc { \
+                CREATE ACCESS POLICY allow_all ALLOW ALL USING (true); \
+              }")
+        .assert().success();
+    SERVER.admin_cmd()
+        .arg("--database=test_list_policies")
+        .arg("list")
+        .arg("policies")
+        .arg("Doc")
+        .assert().success()
+        .stdout(predicates::str::contains("default::Doc\tallow_all"));
+}
+
 #[test]
 fn database_create_wipe_drop() {
     SERVER.admin_cmd()
@@ -115,3 +205,124 @@ fn database_create_wipe_drop() {
         .context("select-again", "make sure that database is not there")
         .failure();
 }
+
+#[test]
+fn database_clone() {
+    SERVER.admin_cmd()
+        .arg("database").arg("create").arg("test_clone_src")
+        .assert()
+        .context("create", "create source database")
+        .success();
+    SERVER.admin_cmd()
+        .arg("query")
+        .arg("--database=test_clone_src")
+        .arg("CREATE TYPE Type1 { CREATE PROPERTY value -> int64 }")
+        .arg("INSERT Type1 { value := 17 }")
+        .assert()
+        .context("add-data", "add some data to the source database")
+        .success();
+
+    SERVER.admin_cmd()
+        .arg("database").arg("clone")
+        .arg("test_clone_src").arg("test_clone_dst")
+        .assert()
+        .context("clone", "clone the database")
+        .success();
+    SERVER.admin_cmd()
+        .arg("query")
+        .arg("--database=test_clone_dst")
+        .arg("SELECT Type1.value")
+        .assert()
+        .context("check-data", "data is copied into the new database")
+        .stdout("17\n").success();
+
+    SERVER.admin_cmd()
+        .arg("database").arg("clone")
+        .arg("test_clone_src").arg("test_clone_dst")
+        .assert()
+        .context("clone-again", "target database must not exist")
+        .failure();
+
+    for name in ["test_clone_src", "test_clone_dst"] {
+        SERVER.admin_cmd()
+            .arg("database").arg("drop").arg(name)
+            .arg("--non-interactive")
+            .assert()
+            .context("drop", "drop database")
+            .success();
+    }
+}
+
+#[test]
+fn json_errors() {
+    let cmd = SERVER.admin_cmd()
+        .arg("query")
+        .arg("--error-format=json")
+        .arg("SELECT 1")
+        .arg("SELECT nonexistent_function()")
+        .assert()
+        .context("json-error", "error is reported as json")
+        .code(1);
+    let err = String::from_utf8(cmd.get_output().stderr.clone()).unwrap();
+    let line = err.lines().last().expect("error is printed");
+    let error: serde_json::Value = serde_json::from_str(line).unwrap();
+    assert_eq!(error["type"], "InvalidReferenceError");
+    assert_eq!(error["filename"], "<query>");
+    assert_eq!(error["statement"]["line"], 1);
+}
+
+#[test]
+fn error_source_name() {
+    SERVER.admin_cmd()
+        .arg("query")
+        .arg("--file=-")
+        .write_stdin("SELECT nonexistent_function();")
+        .assert()
+        .context("stdin-error", "error names the source")
+        .code(1)
+        .stderr(predicates::str::contains("<stdin>:1:"));
+}
+
+#[test]
+fn role_create_alter_drop() {
+    let cmd = SERVER.admin_cmd()
+        .arg("role").arg("create").arg("test_role_01")
+        .arg("--generate-password").arg("--json")
+        .assert()
+        .context("create", "role created with generated password")
+        .success();
+    let out: serde_json::Value = serde_json::from_slice(
+        &cmd.get_output().stdout).unwrap();
+    assert_eq!(out["name"], "test_role_01");
+    assert!(out["password"].as_str().is_some());
+
+    SERVER.admin_cmd()
+        .arg("role").arg("alter").arg("test_role_01")
+        .arg("--rename").arg("test_role_02")
+        .arg("--extending").arg("edgedb")
+        .assert()
+        .context("alter", "rename and extend role")
+        .success();
+    let cmd = SERVER.admin_cmd()
+        .arg("role").arg("show").arg("test_role_02").arg("--json")
+        .assert()
+        .context("show", "show renamed role")
+        .success();
+    let out: serde_json::Value = serde_json::from_slice(
+        &cmd.get_output().stdout).unwrap();
+    assert_eq!(out["superuser"], false);
+    assert_eq!(out["has_password"], true);
+    assert_eq!(out["member_of"], serde_json::json!(["edgedb"]));
+
+    SERVER.admin_cmd()
+        .arg("role").arg("drop").arg("test_role_02")
+        .arg("--non-interactive")
+        .assert()
+        .context("drop", "drop role")
+        .success();
+    SERVER.admin_cmd()
+        .arg("role").arg("show").arg("test_role_02")
+        .assert()
+        .context("show-again", "role does not exist")
+        .failure();
+}
@@ -208,6 +208,60 @@ fn install() {
         .context("query-1-3", "query `inst1` after 2nd upgrade")
         .success();
 
+    Command::new("edgedb")
+        .arg("--instance").arg("inst1")
+        .arg("query").arg("CREATE TYPE Backed { CREATE PROPERTY x -> int64 }")
+        .assert()
+        .context("backup-schema", "create a type to back up")
+        .success();
+
+    for step in ["backup-create-1", "backup-create-2"] {
+        Command::new("edgedb")
+            .arg("instance").arg("backup").arg("create").arg("-I").arg("inst1")
+            .assert()
+            .context(step, "backup `inst1`")
+            .success();
+        // backup ids have a resolution of one second
+        std::thread::sleep(std::time::Duration::from_secs(1));
+    }
+
+    Command::new("edgedb")
+        .arg("instance").arg("backup").arg("list").arg("-I").arg("inst1")
+        .arg("--json")
+        .assert()
+        .context("backup-list", "list backups of `inst1`")
+        .success()
+        .stdout(predicates::str::contains("server_version"));
+
+    Command::new("edgedb")
+        .arg("instance").arg("backup").arg("prune").arg("-I").arg("inst1")
+        .arg("--keep-last=1")
+        .assert()
+        .context("backup-prune", "keep only the latest backup")
+        .success();
+
+    Command::new("edgedb")
+        .arg("--instance").arg("inst1")
+        .arg("query").arg("DROP TYPE Backed")
+        .assert()
+        .context("backup-drop", "drop the backed up type")
+        .success();
+
+    Command::new("edgedb")
+        .arg("instance").arg("backup").arg("restore").arg("-I").arg("inst1")
+        .arg("--non-interactive")
+        .assert()
+        .context("backup-restore", "restore the latest backup")
+        .success();
+
+    Command::new("edgedb")
+        .arg("--instance").arg("inst1")
+        .arg("query").arg("SELECT count(Backed)")
+        .assert()
+        .context("backup-query", "query the restored type")
+        .success()
+        .stdout("0\n");
+
     Command::new("edgedb")
         .arg("instance").arg("destroy").arg("second").arg("--non-interactive")
         .assert()